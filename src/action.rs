@@ -0,0 +1,145 @@
+use heck::{CamelCase, MixedCase};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::apipath::ApiPath;
+use crate::generator::{Operation, OperationInfo, Parameter, RefOr, Responses};
+use crate::oasgen::Oas3Builder;
+use crate::xtests::{tests_extensions, Test};
+
+impl Oas3Builder {
+    /// Registers `POST {web_path}/{action}`, e.g. `POST /api/events/{eid}/publish`.
+    /// The operation id is `publishEvents` for action `publish` and document `Events`.
+    /// Responds with `200` and `O`, or with `204` if `O` is `()`.
+    pub fn action<
+        I: JsonSchema + Serialize,
+        O: JsonSchema + Serialize,
+        E: JsonSchema + Serialize,
+    >(
+        &mut self,
+        web_path: &ApiPath,
+        action: &str,
+        document_name: String,
+        operation_description: Option<String>,
+    ) {
+        self.action_with_tests::<I, O, E>(
+            web_path,
+            action,
+            document_name,
+            operation_description,
+            &[],
+        );
+    }
+
+    /// # Panics
+    ///
+    /// Will panic if json serialization of `tests` fail
+    #[contracts::requires(!action.is_empty())]
+    #[contracts::requires(!action.contains('/'))]
+    #[contracts::requires(!action.contains('{'))]
+    pub fn action_with_tests<
+        I: JsonSchema + Serialize,
+        O: JsonSchema + Serialize,
+        E: JsonSchema + Serialize,
+    >(
+        &mut self,
+        web_path: &ApiPath,
+        action: &str,
+        document_name: String,
+        operation_description: Option<String>,
+        tests: &[Test],
+    ) {
+        let operation_id = format!("{}{document_name}", action.to_mixed_case());
+        let summary = format!("{} {document_name}", action.to_camel_case());
+        let method = http::Method::POST;
+
+        let mut resps = Responses::default();
+
+        let resp = self.create_response::<O>(document_name);
+        let status = if resp.content.is_empty() {
+            "204"
+        } else {
+            "200"
+        };
+        resps.responses.insert(status.to_owned(), resp.into());
+
+        self.add_error_responses::<E>(&mut resps);
+
+        let request_body = self.create_request_body::<I>();
+
+        let mut parameters: Vec<RefOr<Parameter>> = vec![];
+        self.add_path_params(web_path.clone(), &mut parameters);
+        // the key is followed by the action, not at the end of the url
+        if let (None, Some(last)) = (&web_path.token, web_path.ids.last()) {
+            let name = last.key.trim_start_matches('{').trim_end_matches('}');
+            for parameter in &mut parameters {
+                if let RefOr::Object(parameter) = parameter {
+                    if parameter.name == name && parameter.location == "path" {
+                        parameter.description = Some(format!(
+                            "The {} document the `{}` action is performed on, identified by the {} key.",
+                            last.document.to_camel_case(),
+                            action,
+                            last.key
+                        ));
+                    }
+                }
+            }
+        }
+
+        let extensions = tests_extensions(&format!("{web_path}/{action}"), tests);
+
+        self.generator.add_operation(OperationInfo {
+            path: format!("{web_path}/{action}"),
+            method,
+            operation: Operation {
+                operation_id: Some(operation_id),
+                summary: Some(summary),
+                description: operation_description,
+                responses: resps,
+                request_body,
+                parameters,
+                extensions,
+                ..Operation::default()
+            },
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ApiId, ApiPath, Oas3Builder};
+    use schemars::JsonSchema;
+    use serde::Serialize;
+    use serde_json::json;
+
+    #[derive(Serialize, JsonSchema)]
+    pub struct TestEvent {
+        pub title: String,
+    }
+
+    #[test]
+    fn test_action() {
+        let event_path = ApiPath::new(
+            Some("api".to_owned()),
+            vec![ApiId::new("events", "{eid}")],
+            None,
+        );
+        let mut oasb = Oas3Builder::default();
+        oasb.action::<(), TestEvent, String>(&event_path, "publish", "Events".to_owned(), None);
+        oasb.action::<(), (), String>(&event_path, "mark-as-read", "Events".to_owned(), None);
+        let got = serde_json::to_value(oasb.build("1.0.0".to_owned())).unwrap();
+
+        let publish = &got["paths"]["/api/events/{eid}/publish"]["post"];
+        assert_eq!(json!("publishEvents"), publish["operationId"]);
+        assert_eq!(json!("Publish Events"), publish["summary"]);
+        assert!(publish["responses"]["200"]["content"].is_object());
+        assert_eq!(
+            json!("The Events document the `publish` action is performed on, identified by the {eid} key."),
+            publish["parameters"][0]["description"]
+        );
+
+        let mark = &got["paths"]["/api/events/{eid}/mark-as-read"]["post"];
+        assert_eq!(json!("markAsReadEvents"), mark["operationId"]);
+        assert_eq!(json!({"description": "Events"}), mark["responses"]["204"]);
+    }
+}
@@ -1,6 +1,5 @@
 use contracts::requires;
 
-use crate::generator::Parameter;
 use crate::queryparam::QueryParamBuilder;
 
 #[derive(Debug, Clone, Default)]
@@ -33,7 +32,7 @@ pub struct ApiPath {
     pub prefix: Option<String>,
     pub ids: Vec<ApiId>,
     pub token: Option<String>,
-    pub(crate) query_params: Vec<Parameter>,
+    pub(crate) query_params: Vec<QueryParamBuilder>,
 }
 impl std::fmt::Display for ApiPath {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -82,12 +81,11 @@ impl ApiPath {
         token: Option<String>,
         qpbuilders: Vec<QueryParamBuilder>,
     ) -> Self {
-        let query_params = qpbuilders.iter().map(QueryParamBuilder::build).collect();
         ApiPath {
             prefix,
             ids,
             token,
-            query_params,
+            query_params: qpbuilders,
         }
     }
 }
@@ -0,0 +1,267 @@
+use heck::TitleCase;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::apipath::ApiPath;
+use crate::generator::{Operation, OperationInfo, Parameter, RefOr, RequestBody, Responses};
+use crate::oasgen::Oas3Builder;
+use crate::queryparam::QueryParamBuilder;
+use crate::xtests::{tests_extensions, Test};
+
+/// The request body of the batch operations.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BatchRequest<T> {
+    pub items: Vec<T>,
+}
+
+/// The `207 Multi-Status` response body of the batch operations,
+/// with one result per requested item, in request order.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BatchResponse<T, E> {
+    pub results: Vec<BatchItemResult<T, E>>,
+}
+
+/// The outcome of a single item of a batch operation.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BatchItemResult<T, E> {
+    /// The http status code the item would have had as a single request.
+    pub status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub document: Option<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<E>,
+}
+
+impl Oas3Builder {
+    /// Registers `POST {web_path}:batchCreate` taking a `BatchRequest<I>` and
+    /// responding with `207` and a `BatchResponse<O, E>`.
+    pub fn batch_create<
+        I: JsonSchema + Serialize,
+        O: JsonSchema + Serialize,
+        E: JsonSchema + Serialize,
+    >(
+        &mut self,
+        web_path: &ApiPath,
+        document_name: String,
+        operation_description: Option<String>,
+    ) {
+        self.batch_create_with_tests::<I, O, E>(
+            web_path,
+            document_name,
+            operation_description,
+            &[],
+        );
+    }
+
+    /// # Panics
+    ///
+    /// Will panic if json serialization of `tests` fail
+    pub fn batch_create_with_tests<
+        I: JsonSchema + Serialize,
+        O: JsonSchema + Serialize,
+        E: JsonSchema + Serialize,
+    >(
+        &mut self,
+        web_path: &ApiPath,
+        document_name: String,
+        operation_description: Option<String>,
+        tests: &[Test],
+    ) {
+        let request_body = self.create_request_body::<BatchRequest<I>>();
+        self.add_batch_operation::<O, E>(
+            web_path,
+            format!("{web_path}:batchCreate"),
+            http::Method::POST,
+            "batchCreate",
+            document_name,
+            operation_description,
+            request_body,
+            vec![],
+            tests,
+        );
+    }
+
+    /// Registers `PATCH {web_path}:batchUpdate` taking a `BatchRequest<I>` and
+    /// responding with `207` and a `BatchResponse<O, E>`.
+    pub fn batch_update<
+        I: JsonSchema + Serialize,
+        O: JsonSchema + Serialize,
+        E: JsonSchema + Serialize,
+    >(
+        &mut self,
+        web_path: &ApiPath,
+        document_name: String,
+        operation_description: Option<String>,
+    ) {
+        self.batch_update_with_tests::<I, O, E>(
+            web_path,
+            document_name,
+            operation_description,
+            &[],
+        );
+    }
+
+    /// # Panics
+    ///
+    /// Will panic if json serialization of `tests` fail
+    pub fn batch_update_with_tests<
+        I: JsonSchema + Serialize,
+        O: JsonSchema + Serialize,
+        E: JsonSchema + Serialize,
+    >(
+        &mut self,
+        web_path: &ApiPath,
+        document_name: String,
+        operation_description: Option<String>,
+        tests: &[Test],
+    ) {
+        let request_body = self.create_request_body::<BatchRequest<I>>();
+        self.add_batch_operation::<O, E>(
+            web_path,
+            format!("{web_path}:batchUpdate"),
+            http::Method::PATCH,
+            "batchUpdate",
+            document_name,
+            operation_description,
+            request_body,
+            vec![],
+            tests,
+        );
+    }
+
+    /// Registers `DELETE {web_path}?ids=a,b,c` responding with `207` and a `BatchResponse<O, E>`.
+    pub fn batch_delete<O: JsonSchema + Serialize, E: JsonSchema + Serialize>(
+        &mut self,
+        web_path: &ApiPath,
+        document_name: String,
+        operation_description: Option<String>,
+    ) {
+        self.batch_delete_with_tests::<O, E>(web_path, document_name, operation_description, &[]);
+    }
+
+    /// # Panics
+    ///
+    /// Will panic if json serialization of `tests` fail
+    pub fn batch_delete_with_tests<O: JsonSchema + Serialize, E: JsonSchema + Serialize>(
+        &mut self,
+        web_path: &ApiPath,
+        document_name: String,
+        operation_description: Option<String>,
+        tests: &[Test],
+    ) {
+        let ids_param = QueryParamBuilder::new::<Vec<String>>(
+            "ids".to_owned(),
+            Some(vec!["8472".to_owned(), "8473".to_owned()]),
+        )
+        .description(format!("The keys of the {document_name} to delete."))
+        .required(true)
+        .explode(false)
+        .min_items(1);
+        let ids_param = ids_param.build_with(&mut self.generator.schema_generator);
+        self.add_batch_operation::<O, E>(
+            web_path,
+            web_path.to_string(),
+            http::Method::DELETE,
+            "batchDelete",
+            document_name,
+            operation_description,
+            None,
+            vec![ids_param.into()],
+            tests,
+        );
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn add_batch_operation<O: JsonSchema + Serialize, E: JsonSchema + Serialize>(
+        &mut self,
+        web_path: &ApiPath,
+        path: String,
+        method: http::Method,
+        operation_name: &str,
+        document_name: String,
+        operation_description: Option<String>,
+        request_body: Option<RefOr<RequestBody>>,
+        extra_parameters: Vec<RefOr<Parameter>>,
+        tests: &[Test],
+    ) {
+        let operation_id = format!("{operation_name}{document_name}");
+        let summary = format!("{} {document_name}", operation_name.to_title_case());
+
+        let mut resps = Responses::default();
+
+        let status = "207".to_owned();
+        let resp = self.create_response::<BatchResponse<O, E>>(document_name);
+        resps.responses.insert(status, resp.into());
+
+        self.add_error_responses::<E>(&mut resps);
+
+        let mut parameters: Vec<RefOr<Parameter>> = vec![];
+        self.add_path_params(web_path.clone(), &mut parameters);
+        parameters.extend(extra_parameters);
+
+        let extensions = tests_extensions(&path, tests);
+
+        self.generator.add_operation(OperationInfo {
+            path,
+            method,
+            operation: Operation {
+                operation_id: Some(operation_id),
+                summary: Some(summary),
+                description: operation_description,
+                responses: resps,
+                request_body,
+                parameters,
+                extensions,
+                ..Operation::default()
+            },
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ApiPath, Oas3Builder};
+    use schemars::JsonSchema;
+    use serde::Serialize;
+    use serde_json::json;
+
+    #[derive(Serialize, JsonSchema)]
+    pub struct TestEvent {
+        pub title: String,
+    }
+
+    #[test]
+    fn test_batch_operations() {
+        let events_path = ApiPath::new(Some("api".to_owned()), vec![], Some("events".to_owned()));
+        let mut oasb = Oas3Builder::default();
+        oasb.batch_create::<TestEvent, TestEvent, String>(&events_path, "Events".to_owned(), None);
+        oasb.batch_update::<TestEvent, TestEvent, String>(&events_path, "Events".to_owned(), None);
+        oasb.batch_delete::<TestEvent, String>(&events_path, "Events".to_owned(), None);
+        let got = serde_json::to_value(oasb.build("1.0.0".to_owned())).unwrap();
+
+        let create = &got["paths"]["/api/events:batchCreate"]["post"];
+        assert_eq!(json!("batchCreateEvents"), create["operationId"]);
+        assert_eq!(json!("Batch Create Events"), create["summary"]);
+        assert_eq!(
+            json!({"$ref": "#/components/schemas/BatchRequest_for_TestEvent"}),
+            create["requestBody"]["content"]["application/json; charset=utf-8"]["schema"]
+        );
+        assert_eq!(
+            json!({"$ref": "#/components/schemas/BatchResponse_for_TestEvent_and_String"}),
+            create["responses"]["207"]["content"]["application/json; charset=utf-8"]["schema"]
+        );
+        assert!(got["paths"]["/api/events:batchUpdate"]["patch"].is_object());
+
+        let delete = &got["paths"]["/api/events"]["delete"];
+        assert!(delete["requestBody"].is_null());
+        assert_eq!(json!("ids"), delete["parameters"][0]["name"]);
+        assert_eq!(json!(true), delete["parameters"][0]["required"]);
+        assert_eq!(json!(false), delete["parameters"][0]["explode"]);
+        let ids_schema = &delete["parameters"][0]["schema"];
+        assert_eq!(json!(1), ids_schema["minItems"]);
+        assert!(ids_schema["items"]["minLength"].is_null());
+
+        let item_result = &got["components"]["schemas"]["BatchItemResult_for_TestEvent_and_String"];
+        assert_eq!(json!(["status"]), item_result["required"]);
+    }
+}
@@ -0,0 +1,442 @@
+use std::collections::HashMap;
+
+use heck::CamelCase;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::apipath::ApiPath;
+use crate::generator::{
+    Example, Header, Map, OpenApi, OpenApiGenerator, Parameter, Ref, RefOr, RequestBody, Response,
+    Responses,
+};
+use crate::oasgen::Oas3Builder;
+
+impl Oas3Builder {
+    /// Registers `parameter` as `components.parameters.{name}`, returns the reference to it.
+    ///
+    /// Add the returned references to operations with `use_parameter`, `use_response`,
+    /// `use_request_body` and `use_header`; with `dedup_components` enabled, inline
+    /// objects equal to a component are replaced by a reference to it by `build`.
+    pub fn parameter_component(&mut self, name: &str, parameter: Parameter) -> RefOr<Parameter> {
+        let components = self.generator.components_mut();
+        components
+            .parameters
+            .insert(name.to_owned(), parameter.into());
+        component_ref("parameters", name)
+    }
+
+    /// Registers a response with a `T` body as `components.responses.{name}`,
+    /// returns the reference to it.
+    pub fn response_component<T: JsonSchema + Serialize>(
+        &mut self,
+        name: &str,
+        description: String,
+    ) -> RefOr<Response> {
+        let response = self.create_response::<T>(description);
+        let components = self.generator.components_mut();
+        components
+            .responses
+            .insert(name.to_owned(), response.into());
+        component_ref("responses", name)
+    }
+
+    /// Registers a `T` request body as `components.requestBodies.{name}`,
+    /// returns the reference to it.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `T` has no content, e.g. `()`
+    pub fn request_body_component<T: JsonSchema + Serialize>(
+        &mut self,
+        name: &str,
+    ) -> RefOr<RequestBody> {
+        let request_body = self
+            .create_request_body::<T>()
+            .expect("a request body component must have content");
+        let components = self.generator.components_mut();
+        components
+            .request_bodies
+            .insert(name.to_owned(), request_body);
+        component_ref("requestBodies", name)
+    }
+
+    /// Registers `header` as `components.headers.{name}`, returns the reference to it.
+    pub fn header_component(&mut self, name: &str, header: Header) -> RefOr<Header> {
+        let components = self.generator.components_mut();
+        components.headers.insert(name.to_owned(), header.into());
+        component_ref("headers", name)
+    }
+
+    /// Registers `example` as `components.examples.{name}`, returns the reference to it.
+    /// Note that okapi only supports inline examples in parameters and media types.
+    pub fn example_component(&mut self, name: &str, example: Example) -> RefOr<Example> {
+        let components = self.generator.components_mut();
+        components.examples.insert(name.to_owned(), example.into());
+        component_ref("examples", name)
+    }
+
+    /// Adds the `parameter` reference returned by `parameter_component` to the operation
+    /// `method` `web_path`, replacing an inline parameter of the same name and location.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if no operation has been added for `method` `web_path`
+    pub fn use_parameter(
+        &mut self,
+        web_path: &ApiPath,
+        method: &http::Method,
+        parameter: RefOr<Parameter>,
+    ) -> &mut Self {
+        let component = match &parameter {
+            RefOr::Ref(Ref { reference }) => reference
+                .strip_prefix("#/components/parameters/")
+                .and_then(|name| self.generator.components_mut().parameters.get(name))
+                .cloned(),
+            RefOr::Object(_) => None,
+        };
+        let op = self.registered_operation(web_path, method);
+        if let Some(RefOr::Object(component)) = component {
+            op.parameters.retain(|inline| {
+                !matches!(inline, RefOr::Object(inline)
+                    if inline.name == component.name && inline.location == component.location)
+            });
+        }
+        op.parameters.push(parameter);
+        self
+    }
+
+    /// Sets the `status` response of the operation `method` `web_path` to the
+    /// `response` reference returned by `response_component`.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if no operation has been added for `method` `web_path`
+    pub fn use_response(
+        &mut self,
+        web_path: &ApiPath,
+        method: &http::Method,
+        status: u16,
+        response: RefOr<Response>,
+    ) -> &mut Self {
+        let op = self.registered_operation(web_path, method);
+        op.responses.responses.insert(status.to_string(), response);
+        self
+    }
+
+    /// Sets the request body of the operation `method` `web_path` to the
+    /// `request_body` reference returned by `request_body_component`.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if no operation has been added for `method` `web_path`
+    pub fn use_request_body(
+        &mut self,
+        web_path: &ApiPath,
+        method: &http::Method,
+        request_body: RefOr<RequestBody>,
+    ) -> &mut Self {
+        let op = self.registered_operation(web_path, method);
+        op.request_body = Some(request_body);
+        self
+    }
+
+    /// Adds the `header` reference returned by `header_component` as `name` to the
+    /// inline `status` response of the operation `method` `web_path`.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the operation has no inline `status` response
+    pub fn use_header(
+        &mut self,
+        web_path: &ApiPath,
+        method: &http::Method,
+        status: u16,
+        name: &str,
+        header: RefOr<Header>,
+    ) -> &mut Self {
+        let op = self.registered_operation(web_path, method);
+        match op.responses.responses.get_mut(&status.to_string()) {
+            Some(RefOr::Object(response)) => {
+                response.headers.insert(name.to_owned(), header);
+            }
+            _ => panic!(
+                "The operation {} has no inline {} response",
+                op.operation_id.clone().unwrap_or_default(),
+                status
+            ),
+        }
+        self
+    }
+
+    /// When enabled, `build` replaces inline parameters, responses, request bodies
+    /// and headers equal to a registered component with a reference to it, and
+    /// hoists the ones used more than once into components.
+    pub fn dedup_components(&mut self, dedup: bool) -> &mut Self {
+        self.dedup_components = dedup;
+        self
+    }
+}
+
+fn component_ref<T>(kind: &str, name: &str) -> RefOr<T> {
+    RefOr::Ref(Ref {
+        reference: format!("#/components/{kind}/{name}"),
+    })
+}
+
+/// Applies `Oas3Builder::dedup_components` to the finished spec.
+pub(crate) fn dedup_components(openapi: &mut OpenApi) {
+    let OpenApi {
+        paths, components, ..
+    } = openapi;
+    let components = components.get_or_insert_with(Default::default);
+    let mut operations: Vec<_> = paths
+        .values_mut()
+        .flat_map(OpenApiGenerator::path_item_operations)
+        .map(|(_, op)| op)
+        .collect();
+
+    // headers first, as they are inside the responses
+    let headers = operations
+        .iter_mut()
+        .flat_map(|op| {
+            let Responses {
+                default, responses, ..
+            } = &mut op.responses;
+            default.iter_mut().chain(responses.values_mut())
+        })
+        .filter_map(|response| match response {
+            RefOr::Object(response) => Some(response.headers.iter_mut()),
+            RefOr::Ref(_) => None,
+        })
+        .flatten()
+        .map(|(name, header)| (name.clone(), header))
+        .collect();
+    hoist(headers, &mut components.headers, "headers");
+
+    let responses = operations
+        .iter_mut()
+        .flat_map(|op| {
+            let Responses {
+                default, responses, ..
+            } = &mut op.responses;
+            default.iter_mut().chain(responses.values_mut())
+        })
+        .map(|response| {
+            let name = match &response {
+                RefOr::Object(response) => response.description.to_camel_case(),
+                RefOr::Ref(_) => String::new(),
+            };
+            (name, response)
+        })
+        .collect();
+    hoist(responses, &mut components.responses, "responses");
+
+    let request_bodies = operations
+        .iter_mut()
+        .filter_map(|op| op.request_body.as_mut())
+        .map(|request_body| {
+            let name = match &request_body {
+                RefOr::Object(request_body) => request_body
+                    .content
+                    .values()
+                    .find_map(|media| media.schema.as_ref()?.reference.as_ref())
+                    .and_then(|reference| reference.rsplit('/').next())
+                    .map_or_else(|| "RequestBody".to_owned(), ToOwned::to_owned),
+                RefOr::Ref(_) => String::new(),
+            };
+            (name, request_body)
+        })
+        .collect();
+    hoist(
+        request_bodies,
+        &mut components.request_bodies,
+        "requestBodies",
+    );
+
+    let parameters = operations
+        .iter_mut()
+        .flat_map(|op| op.parameters.iter_mut())
+        .map(|parameter| {
+            let name = match &parameter {
+                RefOr::Object(parameter) => parameter.name.clone(),
+                RefOr::Ref(_) => String::new(),
+            };
+            (name, parameter)
+        })
+        .collect();
+    hoist(parameters, &mut components.parameters, "parameters");
+}
+
+/// Replaces the inline `items` equal to a component with a reference to it, and
+/// moves the ones occurring more than once into `components`, named after the
+/// paired name, suffixed with a number when taken. Objects are compared by their
+/// json serialization.
+fn hoist<T: Clone + Serialize>(
+    items: Vec<(String, &mut RefOr<T>)>,
+    components: &mut Map<String, RefOr<T>>,
+    kind: &str,
+) {
+    let key = |object: &T| serde_json::to_string(object).expect("components serialize to json");
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for (_, item) in &items {
+        if let RefOr::Object(object) = &**item {
+            *counts.entry(key(object)).or_default() += 1;
+        }
+    }
+    let mut names: HashMap<String, String> = HashMap::new();
+    for (name, component) in components.iter() {
+        if let RefOr::Object(component) = component {
+            names.entry(key(component)).or_insert_with(|| name.clone());
+        }
+    }
+
+    for (name, item) in items {
+        let RefOr::Object(object) = &*item else {
+            continue;
+        };
+        let object_key = key(object);
+        let component_name = if let Some(component_name) = names.get(&object_key) {
+            component_name.clone()
+        } else {
+            if counts.get(&object_key).copied().unwrap_or(0) < 2 {
+                continue;
+            }
+            let mut component_name = name.clone();
+            let mut suffix = 1;
+            while components.contains_key(&component_name) {
+                suffix += 1;
+                component_name = format!("{name}{suffix}");
+            }
+            components.insert(component_name.clone(), RefOr::Object(object.clone()));
+            names.insert(object_key, component_name.clone());
+            component_name
+        };
+        *item = component_ref(kind, &component_name);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generator::Parameter;
+    use crate::{ApiId, ApiPath, Oas3Builder, QueryParamBuilder};
+    use schemars::JsonSchema;
+    use serde::Serialize;
+    use serde_json::json;
+
+    #[derive(Serialize, JsonSchema)]
+    pub struct TestEvent {
+        pub title: String,
+    }
+
+    #[derive(Serialize, JsonSchema)]
+    pub struct TestNotFound {
+        pub key: String,
+    }
+
+    fn build(dedup: bool) -> serde_json::Value {
+        let event_path = ApiPath::new(
+            Some("api".to_owned()),
+            vec![ApiId::new("events", "{eid}")],
+            None,
+        );
+        let mut oasb = Oas3Builder::default();
+        oasb.dedup_components(dedup);
+        let not_found = oasb.response_component::<TestNotFound>("NotFound", "Not Found".to_owned());
+        assert_eq!(
+            json!({"$ref": "#/components/responses/NotFound"}),
+            serde_json::to_value(not_found).unwrap()
+        );
+        oasb.fetch::<TestEvent, String>(&event_path, "Events".to_owned(), None);
+        oasb.replace::<TestEvent, TestEvent, String>(&event_path, "Events".to_owned(), None);
+        oasb.delete::<TestEvent, TestEvent, String>(&event_path, "Events".to_owned(), None);
+        let got = serde_json::to_value(oasb.build("1.0.0".to_owned())).unwrap();
+        let _openapi_json: openapiv3::OpenAPI = serde_json::from_value(got.clone()).unwrap();
+        got
+    }
+
+    #[test]
+    fn test_use_components() {
+        let event_path = ApiPath::new(
+            Some("api".to_owned()),
+            vec![ApiId::new("events", "{eid}")],
+            None,
+        );
+        let mut oasb = Oas3Builder::default();
+        oasb.replace::<TestEvent, TestEvent, String>(&event_path, "Events".to_owned(), None);
+        let not_found = oasb.response_component::<TestNotFound>("NotFound", "Not Found".to_owned());
+        let body = oasb.request_body_component::<TestEvent>("Event");
+        let eid = QueryParamBuilder::new::<String>("eid".to_owned(), None).build();
+        let eid = oasb.parameter_component(
+            "eid",
+            Parameter {
+                location: "path".to_owned(),
+                required: true,
+                ..eid
+            },
+        );
+        let method = http::Method::PUT;
+        oasb.use_response(&event_path, &method, 404, not_found)
+            .use_request_body(&event_path, &method, body)
+            .use_parameter(&event_path, &method, eid);
+        let got = serde_json::to_value(oasb.build("1.0.0".to_owned())).unwrap();
+        let _openapi_json: openapiv3::OpenAPI = serde_json::from_value(got.clone()).unwrap();
+
+        let put = &got["paths"]["/api/events/{eid}"]["put"];
+        assert_eq!(
+            json!({"$ref": "#/components/responses/NotFound"}),
+            put["responses"]["404"]
+        );
+        assert_eq!(
+            json!({"$ref": "#/components/requestBodies/Event"}),
+            put["requestBody"]
+        );
+        assert_eq!(
+            json!([{"$ref": "#/components/parameters/eid"}]),
+            put["parameters"]
+        );
+    }
+
+    #[test]
+    fn test_inline_without_dedup() {
+        let got = build(false);
+        let fetch = &got["paths"]["/api/events/{eid}"]["get"];
+        assert_eq!(json!("eid"), fetch["parameters"][0]["name"]);
+        assert_eq!(
+            json!("Bad Request"),
+            fetch["responses"]["400"]["description"]
+        );
+        assert!(got["components"]["parameters"].is_null());
+        assert!(got["components"]["responses"]["NotFound"].is_object());
+    }
+
+    #[test]
+    fn test_dedup_components() {
+        let got = build(true);
+        let item = &got["paths"]["/api/events/{eid}"];
+        for method in &["get", "put", "delete"] {
+            assert_eq!(
+                json!({"$ref": "#/components/parameters/eid"}),
+                item[method]["parameters"][0]
+            );
+            assert_eq!(
+                json!({"$ref": "#/components/responses/BadRequest"}),
+                item[method]["responses"]["400"]
+            );
+        }
+        assert_eq!(
+            json!({"$ref": "#/components/requestBodies/TestEvent"}),
+            item["put"]["requestBody"]
+        );
+        let components = &got["components"];
+        assert_eq!(json!("path"), components["parameters"]["eid"]["in"]);
+        assert_eq!(
+            json!("Internal Server Error"),
+            components["responses"]["InternalServerError"]["description"]
+        );
+        assert_eq!(
+            json!({"$ref": "#/components/schemas/TestEvent"}),
+            components["requestBodies"]["TestEvent"]["content"]["application/json; charset=utf-8"]
+                ["schema"]
+        );
+    }
+}
@@ -0,0 +1,246 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+
+use regex::Regex;
+use schemars::gen::SchemaGenerator;
+use schemars::schema::{InstanceType, Schema, SchemaObject, SingleOrVec};
+use serde_json::Value;
+
+/// Value constraints of a parameter, applied to the generated schema when the
+/// parameter is built. Kept apart from the schema because the schema is
+/// regenerated by the `Oas3Builder` the parameter is registered with.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Constraints {
+    pub(crate) minimum: Option<f64>,
+    pub(crate) maximum: Option<f64>,
+    pub(crate) min_length: Option<u32>,
+    pub(crate) max_length: Option<u32>,
+    pub(crate) min_items: Option<u32>,
+    pub(crate) max_items: Option<u32>,
+    pub(crate) pattern: Option<String>,
+    pub(crate) enum_values: Option<Vec<Value>>,
+    // aligned with `enum_values`, emitted as `x-enum-descriptions`
+    pub(crate) enum_descriptions: Vec<String>,
+    pub(crate) default: Option<Value>,
+}
+impl Constraints {
+    fn is_empty(&self) -> bool {
+        self.minimum.is_none()
+            && self.maximum.is_none()
+            && self.min_length.is_none()
+            && self.max_length.is_none()
+            && self.min_items.is_none()
+            && self.max_items.is_none()
+            && self.pattern.is_none()
+            && self.enum_values.is_none()
+            && self.default.is_none()
+    }
+
+    /// Applies the constraints to `schema`, or to its items if it is an array schema;
+    /// `min_items` and `max_items` only apply to array schemas.
+    /// Referenced schemas are inlined to carry the constraints.
+    pub(crate) fn apply(&self, schema: SchemaObject, generator: &SchemaGenerator) -> SchemaObject {
+        if self.is_empty() {
+            return schema;
+        }
+        let mut schema = inline(schema, generator);
+        if let Some(default) = &self.default {
+            schema.metadata().default = Some(default.clone());
+        }
+        let is_array = schema
+            .instance_type
+            .as_ref()
+            .is_some_and(|it| it.contains(&InstanceType::Array));
+        if is_array {
+            if self.min_items.is_some() {
+                schema.array().min_items = self.min_items;
+            }
+            if self.max_items.is_some() {
+                schema.array().max_items = self.max_items;
+            }
+            if let Some(SingleOrVec::Single(items)) = &mut schema.array().items {
+                if let Schema::Object(obj) = items.as_mut() {
+                    *obj = self.apply_to_value(obj.clone(), generator);
+                }
+            }
+            schema
+        } else {
+            self.apply_to_value(schema, generator)
+        }
+    }
+
+    fn apply_to_value(&self, schema: SchemaObject, generator: &SchemaGenerator) -> SchemaObject {
+        let mut schema = inline(schema, generator);
+        if self.minimum.is_some() {
+            schema.number().minimum = self.minimum;
+        }
+        if self.maximum.is_some() {
+            schema.number().maximum = self.maximum;
+        }
+        if self.min_length.is_some() {
+            schema.string().min_length = self.min_length;
+        }
+        if self.max_length.is_some() {
+            schema.string().max_length = self.max_length;
+        }
+        if self.pattern.is_some() {
+            schema.string().pattern.clone_from(&self.pattern);
+        }
+        if let Some(values) = &self.enum_values {
+            // a documented rust enum is a oneOf of single valued enums, replaced by the flat list
+            if schema.subschemas.is_some() {
+                schema.subschemas = None;
+                schema.instance_type = instance_type_of(values).map(SingleOrVec::from);
+            }
+            schema.enum_values = Some(values.clone());
+            if !self.enum_descriptions.is_empty() {
+                schema.extensions.insert(
+                    "x-enum-descriptions".to_owned(),
+                    serde_json::to_value(&self.enum_descriptions).unwrap_or_default(),
+                );
+            }
+        }
+        schema
+    }
+
+    /// Checks `value` against the constraints, array values are checked for their
+    /// number of items and element wise.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` with the reason of the first violated constraint.
+    pub(crate) fn check(&self, value: &Value) -> Result<(), String> {
+        match value {
+            Value::Null => Ok(()),
+            Value::Array(elements) => {
+                if let Some(min_items) = self.min_items {
+                    if elements.len() < min_items as usize {
+                        return Err(format!("{value} has fewer than {min_items} items"));
+                    }
+                }
+                if let Some(max_items) = self.max_items {
+                    if elements.len() > max_items as usize {
+                        return Err(format!("{value} has more than {max_items} items"));
+                    }
+                }
+                elements.iter().try_for_each(|v| self.check(v))
+            }
+            _ => self.check_single(value),
+        }
+    }
+
+    fn check_single(&self, value: &Value) -> Result<(), String> {
+        if let Some(values) = &self.enum_values {
+            if !values.contains(value) {
+                return Err(format!(
+                    "{value} is not one of {}",
+                    Value::from(values.clone())
+                ));
+            }
+        }
+        if let Some(number) = value.as_f64() {
+            if let Some(minimum) = self.minimum {
+                if number < minimum {
+                    return Err(format!("{value} is less than the minimum of {minimum}"));
+                }
+            }
+            if let Some(maximum) = self.maximum {
+                if number > maximum {
+                    return Err(format!("{value} is greater than the maximum of {maximum}"));
+                }
+            }
+        }
+        if let Some(string) = value.as_str() {
+            let len = string.chars().count();
+            if let Some(min_length) = self.min_length {
+                if len < min_length as usize {
+                    return Err(format!("{value} is shorter than {min_length} characters"));
+                }
+            }
+            if let Some(max_length) = self.max_length {
+                if len > max_length as usize {
+                    return Err(format!("{value} is longer than {max_length} characters"));
+                }
+            }
+            // patterns the `regex` crate can not compile are not checked
+            if let Some((pattern, re)) = self
+                .pattern
+                .as_ref()
+                .and_then(|pattern| Some((pattern, compiled_regex(pattern).ok()?)))
+            {
+                if !re.is_match(string) {
+                    return Err(format!("{value} does not match the pattern `{pattern}`"));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `pattern` compiled once per process, patterns are checked for every value.
+pub(crate) fn compiled_regex(pattern: &str) -> Result<Regex, regex::Error> {
+    static CACHE: OnceLock<Mutex<HashMap<String, Regex>>> = OnceLock::new();
+    let mut cache = CACHE
+        .get_or_init(Mutex::default)
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    if let Some(re) = cache.get(pattern) {
+        return Ok(re.clone());
+    }
+    let re = Regex::new(pattern)?;
+    cache.insert(pattern.to_owned(), re.clone());
+    Ok(re)
+}
+
+/// Values and descriptions of a schema generated for a fieldless rust enum.
+/// Variants documented with doc comments are emitted by schemars as a `oneOf`
+/// of single valued enums, undocumented ones get an empty description.
+pub(crate) fn enum_variants(schema: &SchemaObject) -> Option<(Vec<Value>, Vec<String>)> {
+    if let Some(values) = &schema.enum_values {
+        return Some((values.clone(), vec![]));
+    }
+    let one_of = schema.subschemas.as_ref()?.one_of.as_ref()?;
+    let mut values = vec![];
+    let mut descriptions = vec![];
+    for variant in one_of {
+        let variant = match variant {
+            Schema::Object(obj) => obj,
+            Schema::Bool(_) => return None,
+        };
+        match variant.enum_values.as_deref() {
+            Some([value]) => values.push(value.clone()),
+            _ => return None,
+        }
+        descriptions.push(
+            variant
+                .metadata
+                .as_ref()
+                .and_then(|m| m.description.clone())
+                .unwrap_or_default(),
+        );
+    }
+    if descriptions.iter().all(String::is_empty) {
+        descriptions.clear();
+    }
+    Some((values, descriptions))
+}
+
+fn inline(schema: SchemaObject, generator: &SchemaGenerator) -> SchemaObject {
+    let schema = Schema::Object(schema);
+    match generator.dereference(&schema) {
+        Some(Schema::Object(definition)) => definition.clone(),
+        _ => schema.into_object(),
+    }
+}
+
+fn instance_type_of(values: &[Value]) -> Option<InstanceType> {
+    if values.iter().all(Value::is_string) {
+        Some(InstanceType::String)
+    } else if values.iter().all(|v| v.as_i64().is_some()) {
+        Some(InstanceType::Integer)
+    } else if values.iter().all(Value::is_number) {
+        Some(InstanceType::Number)
+    } else {
+        None
+    }
+}
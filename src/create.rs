@@ -1,11 +1,10 @@
 use schemars::JsonSchema;
 use serde::Serialize;
-use serde_json::Value;
 
 use crate::apipath::ApiPath;
-use crate::generator::{Map, Operation, OperationInfo, Parameter, RefOr, Responses};
+use crate::generator::{Operation, OperationInfo, Parameter, RefOr, Responses};
 use crate::oasgen::Oas3Builder;
-use crate::xtests::Test;
+use crate::xtests::{tests_extensions, Test};
 
 impl Oas3Builder {
     pub fn create<
@@ -51,10 +50,7 @@ impl Oas3Builder {
         let mut parameters: Vec<RefOr<Parameter>> = vec![];
         self.add_path_params(web_path.clone(), &mut parameters);
 
-        let mut extensions: Map<String, Value> = Map::default();
-        if !tests.is_empty() {
-            extensions.insert("x-tests".to_owned(), serde_json::to_value(tests).unwrap());
-        }
+        let extensions = tests_extensions(&web_path.to_string(), tests);
 
         self.generator.add_operation(OperationInfo {
             path: web_path.to_string(),
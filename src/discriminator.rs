@@ -0,0 +1,237 @@
+use heck::CamelCase;
+use schemars::schema::{InstanceType, Schema, SchemaObject, SingleOrVec};
+use serde_json::{json, Map, Value};
+
+use crate::generator::OpenApi;
+
+/// Adds a `discriminator` to the `oneOf` components generated for
+/// internally (`#[serde(tag = "..")]`) and adjacently (`#[serde(tag = "..", content = "..")]`)
+/// tagged enums.
+///
+/// Inline variants are moved to their own `{Enum}{Variant}` component, since
+/// the discriminator `mapping` can only point to named schemas.
+///
+/// # Panics
+///
+/// Will panic if a `{Enum}{Variant}` component is already taken by another schema
+pub(crate) fn add_discriminators(openapi: &mut OpenApi) {
+    let Some(components) = &mut openapi.components else {
+        return;
+    };
+    let prefix = "#/components/schemas/";
+
+    let names: Vec<String> = components.schemas.keys().cloned().collect();
+    for name in names {
+        let schema = &components.schemas[&name];
+        let Some(variants) = schema.subschemas.as_ref().and_then(|s| s.one_of.as_ref()) else {
+            continue;
+        };
+        let variants: Vec<SchemaObject> = variants
+            .iter()
+            .filter_map(|variant| match variant {
+                Schema::Object(obj) => Some(obj.clone()),
+                Schema::Bool(_) => None,
+            })
+            .collect();
+        let resolved: Vec<SchemaObject> = variants
+            .iter()
+            .map(|variant| match &variant.reference {
+                Some(reference) => reference
+                    .strip_prefix(prefix)
+                    .and_then(|name| components.schemas.get(name))
+                    .cloned()
+                    .unwrap_or_default(),
+                None => variant.clone(),
+            })
+            .collect();
+        if resolved.is_empty() || resolved.len() != variants.len() {
+            continue;
+        }
+        let Some(property_name) = tag_property(&resolved) else {
+            continue;
+        };
+
+        let mut one_of = vec![];
+        let mut mapping = Map::new();
+        for (variant, definition) in variants.into_iter().zip(&resolved) {
+            let tag = tag_value(definition, &property_name).unwrap_or_default();
+            let reference = if let Some(reference) = variant.reference {
+                reference
+            } else {
+                let variant_name = format!("{name}{}", tag.to_camel_case());
+                assert!(
+                    !components.schemas.contains_key(&variant_name),
+                    "The variant `{}` of `{}` is named `{}` like another schema, rename one with #[schemars(rename = \"..\")]",
+                    tag,
+                    name,
+                    variant_name
+                );
+                components.schemas.insert(variant_name.clone(), variant);
+                format!("{prefix}{variant_name}")
+            };
+            mapping.insert(tag, Value::String(reference.clone()));
+            one_of.push(Schema::Object(SchemaObject::new_ref(reference)));
+        }
+
+        let schema = components.schemas.get_mut(&name).expect("listed above");
+        if let Some(subschemas) = &mut schema.subschemas {
+            subschemas.one_of = Some(one_of);
+        }
+        schema.extensions.insert(
+            "discriminator".to_owned(),
+            json!({"propertyName": property_name, "mapping": mapping}),
+        );
+    }
+}
+
+/// The property every variant requires with a single, distinct string value.
+fn tag_property(variants: &[SchemaObject]) -> Option<String> {
+    let first = variants.first()?.object.as_ref()?;
+    first
+        .properties
+        .keys()
+        .find(|property| {
+            let mut values: Vec<String> = variants
+                .iter()
+                .filter(|variant| {
+                    variant
+                        .object
+                        .as_ref()
+                        .is_some_and(|object| object.required.contains(*property))
+                })
+                .filter_map(|variant| tag_value(variant, property))
+                .collect();
+            let count = values.len();
+            values.sort();
+            values.dedup();
+            count == variants.len() && values.len() == count
+        })
+        .cloned()
+}
+
+fn tag_value(variant: &SchemaObject, property: &str) -> Option<String> {
+    let Some(Schema::Object(tag)) = variant.object.as_ref()?.properties.get(property) else {
+        return None;
+    };
+    if tag.instance_type != Some(SingleOrVec::Single(Box::new(InstanceType::String))) {
+        return None;
+    }
+    match tag.enum_values.as_deref() {
+        Some([Value::String(value)]) => Some(value.clone()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ApiId, ApiPath, Oas3Builder};
+    use schemars::JsonSchema;
+    use serde::Serialize;
+    use serde_json::json;
+
+    #[derive(Serialize, JsonSchema)]
+    pub struct TestCard {
+        pub number: String,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Serialize, JsonSchema)]
+    #[serde(tag = "kind", rename_all = "camelCase")]
+    pub enum TestPayment {
+        Card(TestCard),
+        BankTransfer { iban: String },
+        Cash,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Serialize, JsonSchema)]
+    #[serde(tag = "type", content = "value")]
+    pub enum TestDiscount {
+        Percent(u8),
+        Amount(u64),
+    }
+
+    #[allow(dead_code)]
+    #[derive(Serialize, JsonSchema)]
+    pub enum TestUntagged {
+        Card(TestCard),
+        Cash,
+    }
+
+    #[derive(Serialize, JsonSchema)]
+    pub struct TestOrder {
+        pub payment: TestPayment,
+        pub discount: TestDiscount,
+        pub untagged: TestUntagged,
+    }
+
+    #[test]
+    fn test_tagged_enums() {
+        let order_path = ApiPath::new(
+            Some("api".to_owned()),
+            vec![ApiId::new("orders", "{oid}")],
+            None,
+        );
+        let mut oasb = Oas3Builder::default();
+        oasb.fetch::<TestOrder, String>(&order_path, "Orders".to_owned(), None);
+        let got = serde_json::to_value(oasb.build("1.0.0".to_owned())).unwrap();
+        let _openapi_json: openapiv3::OpenAPI = serde_json::from_value(got.clone()).unwrap();
+        let schemas = &got["components"]["schemas"];
+
+        assert_eq!(
+            json!({
+                "propertyName": "kind",
+                "mapping": {
+                    "card": "#/components/schemas/TestPaymentCard",
+                    "bankTransfer": "#/components/schemas/TestPaymentBankTransfer",
+                    "cash": "#/components/schemas/TestPaymentCash"
+                }
+            }),
+            schemas["TestPayment"]["discriminator"]
+        );
+        assert_eq!(
+            json!([
+                {"$ref": "#/components/schemas/TestPaymentCard"},
+                {"$ref": "#/components/schemas/TestPaymentBankTransfer"},
+                {"$ref": "#/components/schemas/TestPaymentCash"}
+            ]),
+            schemas["TestPayment"]["oneOf"]
+        );
+        assert_eq!(
+            json!(["kind", "number"]),
+            schemas["TestPaymentCard"]["required"]
+        );
+
+        assert_eq!(
+            json!("type"),
+            schemas["TestDiscount"]["discriminator"]["propertyName"]
+        );
+        assert_eq!(
+            json!(["type", "value"]),
+            schemas["TestDiscountPercent"]["required"]
+        );
+
+        assert!(schemas["TestUntagged"]["discriminator"].is_null());
+    }
+
+    #[derive(Serialize, JsonSchema)]
+    #[schemars(rename = "TestPaymentCash")]
+    pub struct TestCashRegister {
+        pub till: u32,
+    }
+
+    #[test]
+    #[should_panic(expected = "is named `TestPaymentCash` like another schema")]
+    fn test_variant_name_collision() {
+        let order_path = ApiPath::new(
+            Some("api".to_owned()),
+            vec![ApiId::new("orders", "{oid}")],
+            None,
+        );
+        let register_path = ApiPath::new(Some("api".to_owned()), vec![], Some("till".to_owned()));
+        let mut oasb = Oas3Builder::default();
+        oasb.fetch::<TestOrder, String>(&order_path, "Orders".to_owned(), None);
+        oasb.fetch::<TestCashRegister, String>(&register_path, "Tills".to_owned(), None);
+        let _spec = oasb.build("1.0.0".to_owned());
+    }
+}
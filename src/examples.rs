@@ -0,0 +1,331 @@
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::apipath::ApiPath;
+use crate::generator::{
+    Example, ExampleValue, Map, MediaType, OpenApi, OpenApiGenerator, Operation, ParameterValue,
+    RefOr,
+};
+use crate::oasgen::Oas3Builder;
+use crate::validation::{SchemaValidator, ValidationError};
+
+/// An example not matching the schema it illustrates, returned by `Oas3Builder::try_build`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExampleError {
+    /// Where the example is, e.g. `GET /api/events/{eid} response 200 example`.
+    pub location: String,
+    /// The first violation of the example.
+    pub error: ValidationError,
+}
+impl std::fmt::Display for ExampleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "The {} does not match its schema at {}",
+            self.location, self.error
+        )
+    }
+}
+impl std::error::Error for ExampleError {}
+
+impl Oas3Builder {
+    /// Sets the example of the request body of the operation `method` `web_path`.
+    pub fn request_example<T: Serialize>(
+        &mut self,
+        web_path: &ApiPath,
+        method: &http::Method,
+        example: &T,
+    ) -> &mut Self {
+        let value = to_example_value(example);
+        for media in request_media(self.registered_operation(web_path, method)) {
+            media.example = Some(value.clone());
+        }
+        self
+    }
+
+    /// Adds the example `name` to the request body of the operation `method` `web_path`.
+    pub fn request_named_example<T: Serialize>(
+        &mut self,
+        web_path: &ApiPath,
+        method: &http::Method,
+        name: &str,
+        summary: Option<String>,
+        example: &T,
+    ) -> &mut Self {
+        let example = named_example(summary, example);
+        for media in request_media(self.registered_operation(web_path, method)) {
+            media
+                .examples
+                .get_or_insert_with(Map::default)
+                .insert(name.to_owned(), example.clone());
+        }
+        self
+    }
+
+    /// Sets the example of the `status` response of the operation `method` `web_path`.
+    pub fn response_example<T: Serialize>(
+        &mut self,
+        web_path: &ApiPath,
+        method: &http::Method,
+        status: u16,
+        example: &T,
+    ) -> &mut Self {
+        let value = to_example_value(example);
+        for media in response_media(self.registered_operation(web_path, method), status) {
+            media.example = Some(value.clone());
+        }
+        self
+    }
+
+    /// Adds the example `name` to the `status` response of the operation `method` `web_path`.
+    pub fn response_named_example<T: Serialize>(
+        &mut self,
+        web_path: &ApiPath,
+        method: &http::Method,
+        status: u16,
+        name: &str,
+        summary: Option<String>,
+        example: &T,
+    ) -> &mut Self {
+        let example = named_example(summary, example);
+        for media in response_media(self.registered_operation(web_path, method), status) {
+            media
+                .examples
+                .get_or_insert_with(Map::default)
+                .insert(name.to_owned(), example.clone());
+        }
+        self
+    }
+}
+
+/// # Panics
+///
+/// Will panic if json serialization of `example` fail
+fn to_example_value<T: Serialize>(example: &T) -> Value {
+    serde_json::to_value(example).unwrap()
+}
+
+fn named_example<T: Serialize>(summary: Option<String>, example: &T) -> Example {
+    Example {
+        summary,
+        description: None,
+        value: ExampleValue::Value(to_example_value(example)),
+        extensions: Map::default(),
+    }
+}
+
+/// The `example` of `media`, else the first inline value of its `examples`.
+pub(crate) fn media_example(media: &MediaType) -> Option<Value> {
+    media.example.clone().or_else(|| {
+        media
+            .examples
+            .iter()
+            .flat_map(|examples| examples.values())
+            .find_map(|example| match &example.value {
+                ExampleValue::Value(value) => Some(value.clone()),
+                ExampleValue::ExternalValue(_) => None,
+            })
+    })
+}
+
+/// # Panics
+///
+/// Will panic if the operation has no request body
+fn request_media(op: &mut Operation) -> impl Iterator<Item = &mut MediaType> {
+    match &mut op.request_body {
+        Some(RefOr::Object(request_body)) => request_body.content.values_mut(),
+        _ => panic!(
+            "The operation {} has no inline request body",
+            op.operation_id.clone().unwrap_or_default()
+        ),
+    }
+}
+
+/// # Panics
+///
+/// Will panic if the operation has no inline `status` response
+fn response_media(op: &mut Operation, status: u16) -> impl Iterator<Item = &mut MediaType> {
+    match op.responses.responses.get_mut(&status.to_string()) {
+        Some(RefOr::Object(response)) => response.content.values_mut(),
+        _ => panic!(
+            "The operation {} has no inline {} response",
+            op.operation_id.clone().unwrap_or_default(),
+            status
+        ),
+    }
+}
+
+/// Validates the examples of the request bodies, responses and parameters of the
+/// finished spec against their schema.
+pub(crate) fn validate_examples(openapi: &mut OpenApi) -> Result<(), ExampleError> {
+    let definitions = openapi
+        .components
+        .as_ref()
+        .map(|components| components.schemas.clone())
+        .unwrap_or_default();
+    let validator = SchemaValidator::new(&definitions);
+    for (path, path_item) in &mut openapi.paths {
+        for (method, op) in OpenApiGenerator::path_item_operations(path_item) {
+            let operation = format!("{method} {path}");
+            if let Some(RefOr::Object(request_body)) = &op.request_body {
+                for media in request_body.content.values() {
+                    let location = format!("{operation} request body");
+                    validate_media(media, &location, &validator)?;
+                }
+            }
+            for (status, response) in &op.responses.responses {
+                if let RefOr::Object(response) = response {
+                    for media in response.content.values() {
+                        let location = format!("{operation} response {status}");
+                        validate_media(media, &location, &validator)?;
+                    }
+                }
+            }
+            for parameter in &op.parameters {
+                if let RefOr::Object(parameter) = parameter {
+                    if let ParameterValue::Schema {
+                        schema,
+                        example,
+                        examples,
+                        ..
+                    } = &parameter.value
+                    {
+                        let location = format!("{operation} parameter `{}`", parameter.name);
+                        validate_all(
+                            schema,
+                            example.as_ref(),
+                            examples.as_ref(),
+                            &location,
+                            &validator,
+                        )?;
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn validate_media(
+    media: &MediaType,
+    location: &str,
+    validator: &SchemaValidator<'_>,
+) -> Result<(), ExampleError> {
+    match &media.schema {
+        Some(schema) => validate_all(
+            schema,
+            media.example.as_ref(),
+            media.examples.as_ref(),
+            location,
+            validator,
+        ),
+        None => Ok(()),
+    }
+}
+
+fn validate_all(
+    schema: &schemars::schema::SchemaObject,
+    example: Option<&Value>,
+    examples: Option<&Map<String, Example>>,
+    location: &str,
+    validator: &SchemaValidator<'_>,
+) -> Result<(), ExampleError> {
+    if let Some(example) = example {
+        validator
+            .validate(example, schema)
+            .map_err(|errors| ExampleError {
+                location: format!("{location} example"),
+                error: errors[0].clone(),
+            })?;
+    }
+    for (name, example) in examples.into_iter().flatten() {
+        if let ExampleValue::Value(example) = &example.value {
+            validator
+                .validate(example, schema)
+                .map_err(|errors| ExampleError {
+                    location: format!("{location} example `{name}`"),
+                    error: errors[0].clone(),
+                })?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ApiId, ApiPath, Oas3Builder};
+    use schemars::JsonSchema;
+    use serde::Serialize;
+    use serde_json::json;
+
+    #[derive(Serialize, JsonSchema)]
+    pub struct TestEvent {
+        pub title: String,
+        pub seats: u32,
+    }
+
+    fn event_path() -> ApiPath {
+        ApiPath::new(
+            Some("api".to_owned()),
+            vec![ApiId::new("events", "{eid}")],
+            None,
+        )
+    }
+
+    #[test]
+    fn test_examples() {
+        let event = TestEvent {
+            title: "Hackaton".to_owned(),
+            seats: 20,
+        };
+        let mut oasb = Oas3Builder::default();
+        oasb.replace::<TestEvent, TestEvent, String>(&event_path(), "Events".to_owned(), None);
+        oasb.request_example(&event_path(), &http::Method::PUT, &event)
+            .response_named_example(
+                &event_path(),
+                &http::Method::PUT,
+                201,
+                "full",
+                Some("A full event".to_owned()),
+                &event,
+            )
+            .response_example(&event_path(), &http::Method::PUT, 400, &"title is missing");
+        let got = serde_json::to_value(oasb.build("1.0.0".to_owned())).unwrap();
+        let _openapi_json: openapiv3::OpenAPI = serde_json::from_value(got.clone()).unwrap();
+
+        let json = "application/json; charset=utf-8";
+        let put = &got["paths"]["/api/events/{eid}"]["put"];
+        assert_eq!(
+            json!({"title": "Hackaton", "seats": 20}),
+            put["requestBody"]["content"][json]["example"]
+        );
+        assert_eq!(
+            json!({"summary": "A full event", "value": {"title": "Hackaton", "seats": 20}}),
+            put["responses"]["201"]["content"][json]["examples"]["full"]
+        );
+        assert_eq!(
+            json!("title is missing"),
+            put["responses"]["400"]["content"][json]["example"]
+        );
+    }
+
+    #[test]
+    fn test_invalid_example() {
+        let mut oasb = Oas3Builder::default();
+        oasb.replace::<TestEvent, TestEvent, String>(&event_path(), "Events".to_owned(), None);
+        oasb.response_named_example(
+            &event_path(),
+            &http::Method::PUT,
+            201,
+            "broken",
+            None,
+            &json!({"title": "Hackaton", "seats": "many"}),
+        );
+        let err = oasb.try_build("1.0.0".to_owned()).unwrap_err();
+        assert_eq!(
+            "PUT /api/events/{eid} response 201 example `broken`",
+            err.location
+        );
+        assert_eq!("/seats", err.error.pointer);
+    }
+}
@@ -1,11 +1,10 @@
 use schemars::JsonSchema;
 use serde::Serialize;
-use serde_json::Value;
 
 use crate::apipath::ApiPath;
-use crate::generator::{Map, Operation, OperationInfo, Parameter, RefOr, Responses};
+use crate::generator::{Operation, OperationInfo, Parameter, RefOr, Responses};
 use crate::oasgen::Oas3Builder;
-use crate::xtests::Test;
+use crate::xtests::{tests_extensions, Test};
 
 impl Oas3Builder {
     pub fn fetch<O: JsonSchema + Serialize, E: JsonSchema + Serialize>(
@@ -19,18 +18,11 @@ impl Oas3Builder {
 
     /// This variant accepts a test spec
     /// ```
-    /// use oas_gen::xtests::{Test,Response};
-    /// let mut params = std::collections::BTreeMap::default();
-    /// params.insert("key".to_owned(), "8472".to_owned());
-    /// let test_ok = Test {
-    ///     description: "Fetch a document by key.".to_owned(),
-    ///     response: Response {
-    ///         status: vec![200],
-    ///         ..Response::default()
-    ///     },
-    ///     params,
-    ///     ..Test::default()
-    /// };
+    /// use oas_gen::xtests::Test;
+    /// let test_ok = Test::new("Fetch a document by key.")
+    ///     .param("key", "8472")
+    ///     .expect_status(200)
+    ///     .build();
     /// assert_eq!(r#"{"description":"Fetch a document by key.","params":{"key":"8472"},"response":{"status":[200]}}"#, serde_json::to_string(&test_ok).unwrap());
     /// let tests = vec![test_ok];
     /// ```
@@ -59,10 +51,7 @@ impl Oas3Builder {
         let mut parameters: Vec<RefOr<Parameter>> = vec![];
         self.add_path_params(web_path.clone(), &mut parameters);
 
-        let mut extensions: Map<String, Value> = Map::default();
-        if !tests.is_empty() {
-            extensions.insert("x-tests".to_owned(), serde_json::to_value(tests).unwrap());
-        }
+        let extensions = tests_extensions(&web_path.to_string(), tests);
 
         self.generator.add_operation(OperationInfo {
             path: web_path.to_string(),
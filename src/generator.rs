@@ -1,10 +1,13 @@
 use http::Method;
 use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::visit::Visitor;
 // use schemars::schema::SchemaObject;
 pub use okapi::openapi3::{Components, OpenApi, Operation, PathItem, *};
 
 use std::collections::{hash_map::Entry as HashEntry, HashMap};
 
+use crate::oasgen::Oas3Builder;
+
 pub type Map<K, V> = schemars::Map<K, V>;
 // pub type SecurityRequirement = Map<String, Vec<String>>;
 
@@ -43,7 +46,24 @@ impl OpenApiGenerator {
         };
     }
 
-    pub fn into_openapi(self) -> OpenApi {
+    pub fn components_mut(&mut self) -> &mut Components {
+        &mut self.components
+    }
+
+    pub fn operation_mut(&mut self, path: &str, method: &http::Method) -> Option<&mut Operation> {
+        self.operations.get_mut(&(path.to_owned(), method.clone()))
+    }
+
+    pub fn into_openapi(mut self) -> OpenApi {
+        let mut definitions = self.schema_generator.definitions().clone();
+        for visitor in self.schema_generator.visitors_mut() {
+            for schema in definitions.values_mut() {
+                visitor.visit_schema(schema);
+            }
+            for op in self.operations.values_mut() {
+                Self::visit_operation(visitor, op);
+            }
+        }
         OpenApi {
             openapi: "3.0.0".to_owned(),
             paths: {
@@ -55,11 +75,9 @@ impl OpenApiGenerator {
                 paths
             },
             components: Some(Components {
-                schemas: self
-                    .schema_generator
-                    .definitions()
-                    .iter()
-                    .map(|(k, v)| (k.clone(), v.clone().into()))
+                schemas: definitions
+                    .into_iter()
+                    .map(|(k, v)| (k, v.into()))
                     .collect::<Map<_, _>>(),
                 ..self.components
             }),
@@ -67,6 +85,128 @@ impl OpenApiGenerator {
         }
     }
 
+    /// Applies `visitor` to the parameter, request body and response schemas of `op`.
+    pub(crate) fn visit_operation<V: Visitor + ?Sized>(visitor: &mut V, op: &mut Operation) {
+        if let Some(RefOr::Object(request_body)) = &mut op.request_body {
+            Self::visit_content(visitor, &mut request_body.content);
+        }
+        for response in op.responses.responses.values_mut() {
+            if let RefOr::Object(response) = response {
+                Self::visit_response(visitor, response);
+            }
+        }
+        for parameter in &mut op.parameters {
+            if let RefOr::Object(parameter) = parameter {
+                Self::visit_parameter_value(visitor, &mut parameter.value);
+            }
+        }
+    }
+
+    /// Applies `visitor` to the schemas of the responses, parameters, request bodies
+    /// and headers of `components`, its `schemas` are left to the caller.
+    pub(crate) fn visit_components<V: Visitor + ?Sized>(
+        visitor: &mut V,
+        components: &mut Components,
+    ) {
+        for response in components.responses.values_mut() {
+            if let RefOr::Object(response) = response {
+                Self::visit_response(visitor, response);
+            }
+        }
+        for parameter in components.parameters.values_mut() {
+            if let RefOr::Object(parameter) = parameter {
+                Self::visit_parameter_value(visitor, &mut parameter.value);
+            }
+        }
+        for request_body in components.request_bodies.values_mut() {
+            if let RefOr::Object(request_body) = request_body {
+                Self::visit_content(visitor, &mut request_body.content);
+            }
+        }
+        for header in components.headers.values_mut() {
+            if let RefOr::Object(header) = header {
+                Self::visit_parameter_value(visitor, &mut header.value);
+            }
+        }
+    }
+
+    fn visit_response<V: Visitor + ?Sized>(visitor: &mut V, response: &mut Response) {
+        Self::visit_content(visitor, &mut response.content);
+        for header in response.headers.values_mut() {
+            if let RefOr::Object(header) = header {
+                Self::visit_parameter_value(visitor, &mut header.value);
+            }
+        }
+    }
+
+    fn visit_content<V: Visitor + ?Sized>(visitor: &mut V, content: &mut Map<String, MediaType>) {
+        for media in content.values_mut() {
+            if let Some(schema) = &mut media.schema {
+                visitor.visit_schema_object(schema);
+            }
+        }
+    }
+
+    fn visit_parameter_value<V: Visitor + ?Sized>(visitor: &mut V, value: &mut ParameterValue) {
+        if let ParameterValue::Schema { schema, .. } = value {
+            visitor.visit_schema_object(schema);
+        }
+    }
+
+    /// The operations of `path_item` with their method.
+    pub(crate) fn path_item_operations(path_item: &mut PathItem) -> Vec<(Method, &mut Operation)> {
+        let PathItem {
+            get,
+            put,
+            post,
+            delete,
+            options,
+            head,
+            patch,
+            trace,
+            ..
+        } = path_item;
+        IntoIterator::into_iter([
+            (Method::GET, get),
+            (Method::PUT, put),
+            (Method::POST, post),
+            (Method::DELETE, delete),
+            (Method::OPTIONS, options),
+            (Method::HEAD, head),
+            (Method::PATCH, patch),
+            (Method::TRACE, trace),
+        ])
+        .filter_map(|(method, op)| op.as_mut().map(|op| (method, op)))
+        .collect()
+    }
+
+    /// The operations of `path_item` with their method, see `path_item_operations`.
+    pub(crate) fn path_item_operation_refs(path_item: &PathItem) -> Vec<(Method, &Operation)> {
+        let PathItem {
+            get,
+            put,
+            post,
+            delete,
+            options,
+            head,
+            patch,
+            trace,
+            ..
+        } = path_item;
+        IntoIterator::into_iter([
+            (Method::GET, get),
+            (Method::PUT, put),
+            (Method::POST, post),
+            (Method::DELETE, delete),
+            (Method::OPTIONS, options),
+            (Method::HEAD, head),
+            (Method::PATCH, patch),
+            (Method::TRACE, trace),
+        ])
+        .filter_map(|(method, op)| op.as_ref().map(|op| (method, op)))
+        .collect()
+    }
+
     fn add_operation_to_path_item(path_item: &mut PathItem, method: &http::Method, op: Operation) {
         // use http::Method::*;
         let option = match *method {
@@ -176,10 +316,13 @@ pub struct OperationInfo {
 
 impl From<OpenApi> for OpenApiGenerator {
     fn from(openapi: OpenApi) -> Self {
-        let mut sts = SchemaSettings::openapi3();
-        sts.option_add_null_type = false;
-        sts.option_nullable = false;
-        let generator = SchemaGenerator::new(sts);
+        Self::from_openapi_with_settings(openapi, Oas3Builder::default_settings())
+    }
+}
+
+impl OpenApiGenerator {
+    pub fn from_openapi_with_settings(openapi: OpenApi, settings: SchemaSettings) -> Self {
+        let generator = SchemaGenerator::new(settings);
 
         let mut openapigenerator = OpenApiGenerator {
             schema_generator: generator,
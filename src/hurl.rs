@@ -0,0 +1,504 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use heck::SnakeCase;
+use serde_json::Value;
+
+use crate::generator::{Map, OpenApi, Operation};
+use crate::runner::encode_param;
+use crate::xtests::{
+    captured_names, operation_of, order_tests, substitute, substitute_value, tested_operations,
+    BodyAssertion, Hook, Request, Test, TestOrderError, TestedOperation,
+};
+
+/// How `HurlExporter` splits the tests into files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HurlGrouping {
+    /// One file per first tag of the operations, `default.hurl` for untagged ones.
+    #[default]
+    Tag,
+    /// One file per operation, named after the operation id.
+    Operation,
+}
+
+/// Exports the `x-tests` of a spec as [Hurl](https://hurl.dev) files.
+///
+/// Requests go to `{{base_url}}` and tests with `auth` send `{{token}}` as a bearer
+/// token, both supplied as Hurl variables, e.g. `hurl --variable base_url=... --test *.hurl`.
+/// Captured variables become Hurl captures, so a test depending on other tests is
+/// written to the file of the first test of its dependency chain.
+/// ```
+/// # use oas_gen::{HurlExporter, HurlGrouping, Oas3Builder};
+/// # let openapi = Oas3Builder::default().build("1.0.0".to_owned());
+/// let files = HurlExporter::new()
+///     .grouping(HurlGrouping::Operation)
+///     .export(&openapi)
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct HurlExporter {
+    grouping: HurlGrouping,
+    base_url_variable: String,
+    token_variable: String,
+}
+
+impl Default for HurlExporter {
+    fn default() -> Self {
+        HurlExporter::new()
+    }
+}
+
+impl HurlExporter {
+    #[must_use]
+    pub fn new() -> Self {
+        HurlExporter {
+            grouping: HurlGrouping::default(),
+            base_url_variable: "base_url".to_owned(),
+            token_variable: "token".to_owned(),
+        }
+    }
+
+    #[must_use]
+    pub fn grouping(mut self, grouping: HurlGrouping) -> Self {
+        self.grouping = grouping;
+        self
+    }
+
+    /// Renames the Hurl variables of the base url and the auth token.
+    #[must_use]
+    pub fn variables(mut self, base_url: &str, token: &str) -> Self {
+        base_url.clone_into(&mut self.base_url_variable);
+        token.clone_into(&mut self.token_variable);
+        self
+    }
+
+    /// The Hurl files by file name, the tests in running order, see `xtests::order_tests`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the dependencies between the tests can not be satisfied.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if an `x-tests` extension is not a list of tests
+    pub fn export(&self, openapi: &OpenApi) -> Result<Map<String, String>, TestOrderError> {
+        let operations = tested_operations(openapi);
+
+        // every captured name becomes a Hurl template
+        let variables: BTreeMap<String, String> = captured_names(&operations)
+            .iter()
+            .map(|name| (name.clone(), format!("{{{{{name}}}}}")))
+            .collect();
+
+        let all = operations.iter().flat_map(|op| &op.tests);
+        let ordered = order_tests(all)?;
+        let chains = dependency_chains(&ordered);
+        let names: Vec<String> = ordered
+            .iter()
+            .map(|test| self.file_name(&operation_of(&operations, test).operation))
+            .collect();
+        let mut files: Map<String, String> = Map::default();
+        for (index, test) in ordered.iter().enumerate() {
+            let TestedOperation { path, method, .. } = operation_of(&operations, test);
+            let file = &names[chains[index]];
+            let content = files.entry(format!("{file}.hurl")).or_default();
+            if test.skip {
+                let _ = writeln!(content, "# skipped: {}\n", test.description);
+                continue;
+            }
+            for hook in &test.setup {
+                self.write_hook(content, "setup", hook, &variables);
+            }
+            let _ = writeln!(content, "# {}", test.description);
+            let hook = Hook {
+                method: method.to_string(),
+                path: path.clone(),
+                auth: test.auth,
+                params: test.params.clone(),
+                request: test.request.clone(),
+                capture: test.capture.clone(),
+            };
+            self.write_request(content, &hook, &variables);
+            write_response(content, test, &variables);
+            for hook in &test.teardown {
+                self.write_hook(content, "teardown", hook, &variables);
+            }
+        }
+        Ok(files)
+    }
+
+    fn file_name(&self, op: &Operation) -> String {
+        match self.grouping {
+            HurlGrouping::Tag => op
+                .tags
+                .first()
+                .map_or_else(|| "default".to_owned(), |tag| tag.to_snake_case()),
+            HurlGrouping::Operation => op.operation_id.clone().unwrap_or_default().to_snake_case(),
+        }
+    }
+
+    /// Writes the files of `export` into `dir`, returns their paths.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the tests can not be ordered or a file can not be written.
+    pub fn write_to(&self, openapi: &OpenApi, dir: &Path) -> io::Result<Vec<PathBuf>> {
+        let files = self
+            .export(openapi)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        std::fs::create_dir_all(dir)?;
+        let mut paths = vec![];
+        for (name, content) in files {
+            let path = dir.join(name);
+            std::fs::write(&path, content)?;
+            paths.push(path);
+        }
+        Ok(paths)
+    }
+
+    fn write_hook(
+        &self,
+        content: &mut String,
+        kind: &str,
+        hook: &Hook,
+        variables: &BTreeMap<String, String>,
+    ) {
+        let _ = writeln!(content, "# {kind}: {} {}", hook.method, hook.path);
+        self.write_request(content, hook, variables);
+        let _ = writeln!(content, "HTTP *");
+        let _ = writeln!(content, "[Asserts]\nstatus >= 200\nstatus < 300");
+        write_captures(content, &hook.capture);
+        let _ = writeln!(content);
+    }
+
+    fn write_request(
+        &self,
+        content: &mut String,
+        hook: &Hook,
+        variables: &BTreeMap<String, String>,
+    ) {
+        let mut path = hook.path.clone();
+        for (name, value) in &hook.params {
+            path = path.replace(
+                &format!("{{{name}}}"),
+                &encode_param(value, |name| variables.contains_key(name)),
+            );
+        }
+        let path = substitute(&path, variables);
+        let _ = writeln!(
+            content,
+            "{} {{{{{}}}}}{}",
+            hook.method, self.base_url_variable, path
+        );
+
+        let Request {
+            body,
+            cookie,
+            headers,
+            query,
+        } = &hook.request;
+        if hook.auth {
+            let _ = writeln!(
+                content,
+                "Authorization: Bearer {{{{{}}}}}",
+                self.token_variable
+            );
+        }
+        for (name, value) in headers {
+            let _ = writeln!(content, "{name}: {}", substitute(value, variables));
+        }
+        if !cookie.is_empty() {
+            let _ = writeln!(content, "Cookie: {}", substitute(cookie, variables));
+        }
+        let json_body = matches!(body, Value::Object(_) | Value::Array(_));
+        if !body.is_null() && !json_body {
+            let _ = writeln!(content, "Content-Type: application/json");
+        }
+        if !query.is_empty() {
+            let _ = writeln!(content, "[QueryStringParams]");
+            for (name, value) in query {
+                let _ = writeln!(content, "{name}: {}", substitute(value, variables));
+            }
+        }
+        if !body.is_null() {
+            let body = substitute_value(body, variables);
+            let json = serde_json::to_string_pretty(&body).unwrap_or_default();
+            if json_body {
+                let _ = writeln!(content, "{json}");
+            } else {
+                let _ = writeln!(content, "```\n{json}\n```");
+            }
+        }
+    }
+}
+
+fn write_response(content: &mut String, test: &Test, variables: &BTreeMap<String, String>) {
+    let response = &test.response;
+    match response.status.as_slice() {
+        [status] => {
+            let _ = writeln!(content, "HTTP {status}");
+        }
+        _ => {
+            let _ = writeln!(content, "HTTP *");
+        }
+    }
+    for (name, value) in &response.headers {
+        let _ = writeln!(content, "{name}: {}", substitute(value, variables));
+    }
+    write_captures(content, &test.capture);
+
+    let mut asserts = vec![];
+    if let (Some(min), Some(max)) = (response.status.iter().min(), response.status.iter().max()) {
+        if min != max {
+            asserts.push(format!("status >= {min}"));
+            asserts.push(format!("status <= {max}"));
+            if (*min..=*max).any(|status| !response.status.contains(&status)) {
+                let statuses: Vec<_> = response.status.iter().map(ToString::to_string).collect();
+                asserts.push(format!(
+                    "# the status must be one of {}, not checked by Hurl",
+                    statuses.join(", ")
+                ));
+            }
+        }
+    }
+    for (name, value) in &response.cookie {
+        let value = substitute(value, variables);
+        asserts.push(format!("cookie \"{name}\" == {}", Value::String(value)));
+    }
+    for name in &test.required {
+        asserts.push(format!("jsonpath \"$.{name}\" exists"));
+    }
+    let mut exact_body = None;
+    for assertion in &response.body {
+        match assertion {
+            BodyAssertion::Exact(body) => exact_body = Some(substitute_value(body, variables)),
+            BodyAssertion::Subset(expected) => {
+                subset_asserts(&substitute_value(expected, variables), "$", &mut asserts);
+            }
+            BodyAssertion::PointerEquals(pointer, expected) => {
+                let path = pointer_to_jsonpath(pointer);
+                subset_asserts(&substitute_value(expected, variables), &path, &mut asserts);
+            }
+            BodyAssertion::MatchesSchema | BodyAssertion::Schema(_) => {
+                asserts.push("# the body must match its schema, not checked by Hurl".to_owned());
+            }
+        }
+    }
+    if !asserts.is_empty() {
+        let _ = writeln!(content, "[Asserts]\n{}", asserts.join("\n"));
+    }
+    if let Some(body) = exact_body {
+        let json = serde_json::to_string_pretty(&body).unwrap_or_default();
+        if matches!(body, Value::Object(_) | Value::Array(_)) {
+            let _ = writeln!(content, "{json}");
+        } else {
+            let _ = writeln!(content, "```\n{json}\n```");
+        }
+    }
+    let _ = writeln!(content);
+}
+
+/// For every test of `ordered`, the index of the first test of its dependency chain,
+/// tests sharing a dependency belong to the same chain.
+fn dependency_chains(ordered: &[&Test]) -> Vec<usize> {
+    fn first(chains: &[usize], mut index: usize) -> usize {
+        while chains[index] != index {
+            index = chains[index];
+        }
+        index
+    }
+
+    let mut chains: Vec<usize> = (0..ordered.len()).collect();
+    for (index, test) in ordered.iter().enumerate() {
+        for dependency in &test.depends_on {
+            if let Some(other) = ordered.iter().position(|t| &t.id == dependency) {
+                let (a, b) = (first(&chains, index), first(&chains, other));
+                chains[a.max(b)] = a.min(b);
+            }
+        }
+    }
+    (0..ordered.len())
+        .map(|index| first(&chains, index))
+        .collect()
+}
+
+fn write_captures(content: &mut String, captures: &BTreeMap<String, String>) {
+    if captures.is_empty() {
+        return;
+    }
+    let _ = writeln!(content, "[Captures]");
+    for (name, path) in captures {
+        let path = if path.starts_with('/') || path.is_empty() {
+            pointer_to_jsonpath(path)
+        } else {
+            path.clone()
+        };
+        let _ = writeln!(content, "{name}: jsonpath \"{path}\"");
+    }
+}
+
+/// Asserts every scalar of `expected` at `path`; scalar array items must be included.
+fn subset_asserts(expected: &Value, path: &str, asserts: &mut Vec<String>) {
+    match expected {
+        Value::Object(object) => {
+            for (name, value) in object {
+                subset_asserts(value, &format!("{path}['{name}']"), asserts);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                let assert = match item {
+                    Value::Object(_) | Value::Array(_) => {
+                        format!("# {path} must contain {item}, not checked by Hurl")
+                    }
+                    _ => format!("jsonpath \"{path}\" includes {item}"),
+                };
+                asserts.push(assert);
+            }
+        }
+        _ => asserts.push(format!("jsonpath \"{path}\" == {expected}")),
+    }
+}
+
+/// `/items/0/id` as `$['items'][0]['id']`.
+fn pointer_to_jsonpath(pointer: &str) -> String {
+    let mut path = "$".to_owned();
+    for token in pointer.split('/').skip(1) {
+        let token = token.replace("~1", "/").replace("~0", "~");
+        if token.parse::<usize>().is_ok() {
+            let _ = write!(path, "[{token}]");
+        } else {
+            let _ = write!(path, "['{token}']");
+        }
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HurlExporter, HurlGrouping};
+    use crate::xtests::{BodyAssertion, Test};
+    use crate::{ApiId, ApiPath, Oas3Builder};
+    use schemars::JsonSchema;
+    use serde::Serialize;
+    use serde_json::json;
+
+    #[derive(Serialize, JsonSchema)]
+    pub struct TestEvent {
+        pub id: String,
+        pub title: String,
+    }
+
+    #[test]
+    fn test_export() {
+        let events_path = ApiPath::new(Some("api".to_owned()), vec![], Some("events".to_owned()));
+        let event_path = ApiPath::new(
+            Some("api".to_owned()),
+            vec![ApiId::new("events", "{eid}")],
+            None,
+        );
+        let create = Test::new("Create an event.")
+            .id("create")
+            .auth()
+            .json_body(&json!({"title": "Hackaton"}))
+            .expect_status(201)
+            .capture("eid", "$.id")
+            .build();
+        let fetch = Test::new("Fetch the created event.")
+            .depends_on("create")
+            .param("eid", "{eid}")
+            .query("fields", "title")
+            .expect_status(200)
+            .expect_status(201)
+            .expect_header("content-type", "application/json")
+            .expect_body(BodyAssertion::Subset(json!({"id": "{eid}", "tags": ["a"]})))
+            .expect_body(BodyAssertion::MatchesSchema)
+            .build();
+        let mut oasb = Oas3Builder::default();
+        oasb.fetch_with_tests::<TestEvent, String>(
+            &event_path,
+            "Events".to_owned(),
+            None,
+            &[fetch],
+        );
+        oasb.create_with_tests::<TestEvent, TestEvent, String>(
+            &events_path,
+            "Events".to_owned(),
+            None,
+            &[create],
+        );
+        let openapi = oasb.build("1.0.0".to_owned());
+
+        let files = HurlExporter::new().export(&openapi).unwrap();
+        let expected = r#"# Create an event.
+POST {{base_url}}/api/events
+Authorization: Bearer {{token}}
+{
+  "title": "Hackaton"
+}
+HTTP 201
+[Captures]
+eid: jsonpath "$.id"
+
+# Fetch the created event.
+GET {{base_url}}/api/events/{{eid}}
+[QueryStringParams]
+fields: title
+HTTP *
+content-type: application/json
+[Asserts]
+status >= 200
+status <= 201
+jsonpath "$['id']" == "{{eid}}"
+jsonpath "$['tags']" includes "a"
+# the body must match its schema, not checked by Hurl
+
+"#;
+        similar_asserts::assert_eq!(expected, files["default.hurl"]);
+
+        let files = HurlExporter::new()
+            .grouping(HurlGrouping::Operation)
+            .export(&openapi)
+            .unwrap();
+        // the fetch depends on the create, it stays in its file
+        let names: Vec<_> = files.keys().map(String::as_str).collect();
+        assert_eq!(vec!["create_events.hurl"], names);
+        similar_asserts::assert_eq!(expected, files["create_events.hurl"]);
+    }
+
+    #[test]
+    fn test_status_and_param() {
+        let event_path = ApiPath::new(
+            Some("api".to_owned()),
+            vec![ApiId::new("events", "{eid}")],
+            None,
+        );
+        let fetch = Test::new("Fetch an event.")
+            .param("eid", "2024/{year} Hackaton")
+            .expect_status(200)
+            .expect_status(404)
+            .build();
+        let mut oasb = Oas3Builder::default();
+        oasb.fetch_with_tests::<TestEvent, String>(
+            &event_path,
+            "Events".to_owned(),
+            None,
+            &[fetch],
+        );
+        let openapi = oasb.build("1.0.0".to_owned());
+
+        let files = HurlExporter::new().export(&openapi).unwrap();
+        let expected = r"# Fetch an event.
+GET {{base_url}}/api/events/2024%2F%7Byear%7D%20Hackaton
+HTTP *
+[Asserts]
+status >= 200
+status <= 404
+# the status must be one of 200, 404, not checked by Hurl
+
+";
+        similar_asserts::assert_eq!(expected, files["default.hurl"]);
+    }
+}
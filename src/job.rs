@@ -0,0 +1,332 @@
+use heck::CamelCase;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::apipath::ApiPath;
+use crate::generator::{
+    Header, Link, Map, Operation, OperationInfo, Parameter, ParameterValue, RefOr, Responses,
+};
+use crate::oasgen::Oas3Builder;
+use crate::xtests::{tests_extensions, Test};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum JobStatus {
+    Pending,
+    Running,
+    Succeeded,
+    Failed,
+}
+
+/// The status of a long-running operation, returned by the triggering operation
+/// and by the job resource.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct Job<T> {
+    pub id: String,
+    pub status: JobStatus,
+    /// Set once the job has `succeeded`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<T>,
+    /// Set once the job has `failed`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl Oas3Builder {
+    /// Registers an operation that starts a job and responds with `202 Accepted`,
+    /// a `Job<O>` body and a `Location` header pointing to `job_path`.
+    ///
+    /// `job_path` is the item path of the job resource, e.g. `/api/jobs/{jobId}`.
+    /// Unless already registered, `GET` on it is registered too, responding with
+    /// the `Job<O>`, and the `202` response links to it.
+    /// Register a distinct `job_path` per result type `O`.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `job_path` is already registered with another result type
+    pub fn async_operation<
+        I: JsonSchema + Serialize,
+        O: JsonSchema + Serialize,
+        E: JsonSchema + Serialize,
+    >(
+        &mut self,
+        web_path: &ApiPath,
+        method: http::Method,
+        job_path: &ApiPath,
+        document_name: String,
+        operation_name: &str,
+        operation_description: Option<String>,
+    ) {
+        self.async_operation_with_tests::<I, O, E>(
+            web_path,
+            method,
+            job_path,
+            document_name,
+            operation_name,
+            operation_description,
+            &[],
+        );
+    }
+
+    /// # Panics
+    ///
+    /// Will panic if json serialization of `tests` fail,
+    /// or if `job_path` is already registered with another result type
+    #[allow(clippy::too_many_arguments)]
+    #[contracts::requires(job_path.token.is_none(), "job_path must end with the job key")]
+    #[contracts::requires(!job_path.ids.is_empty(), "job_path must end with the job key")]
+    pub fn async_operation_with_tests<
+        I: JsonSchema + Serialize,
+        O: JsonSchema + Serialize,
+        E: JsonSchema + Serialize,
+    >(
+        &mut self,
+        web_path: &ApiPath,
+        method: http::Method,
+        job_path: &ApiPath,
+        document_name: String,
+        operation_name: &str,
+        operation_description: Option<String>,
+        tests: &[Test],
+    ) {
+        let job_operation_id = self.add_job_resource::<O, E>(job_path);
+
+        let operation_id = format!("{operation_name}{document_name}");
+
+        let mut resps = Responses::default();
+
+        let status = "202".to_owned();
+        let mut resp = self.create_response::<Job<O>>(document_name);
+        resp.headers.insert(
+            "Location".to_owned(),
+            self.create_location_header(job_path).into(),
+        );
+        let job_key = job_path
+            .ids
+            .last()
+            .map(|id| id.key.clone())
+            .unwrap_or_default();
+        let mut link_parameters = Map::default();
+        link_parameters.insert(
+            job_key
+                .trim_start_matches('{')
+                .trim_end_matches('}')
+                .to_owned(),
+            Value::String("$response.body#/id".to_owned()),
+        );
+        resp.links.insert(
+            job_operation_id.to_camel_case(),
+            Link {
+                operation_id: Some(job_operation_id),
+                parameters: link_parameters,
+                description: Some("The status of the started job.".to_owned()),
+                ..Link::default()
+            }
+            .into(),
+        );
+        resps.responses.insert(status, resp.into());
+
+        self.add_error_responses::<E>(&mut resps);
+
+        let request_body = self.create_request_body::<I>();
+
+        let mut parameters: Vec<RefOr<Parameter>> = vec![];
+        self.add_path_params(web_path.clone(), &mut parameters);
+
+        let extensions = tests_extensions(&web_path.to_string(), tests);
+
+        self.generator.add_operation(OperationInfo {
+            path: web_path.to_string(),
+            method,
+            operation: Operation {
+                operation_id: Some(operation_id),
+                description: operation_description,
+                responses: resps,
+                request_body,
+                parameters,
+                extensions,
+                ..Operation::default()
+            },
+        });
+    }
+
+    /// Registers `GET job_path` unless already registered, returns its operation id.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `GET job_path` responds with a job of another result type
+    fn add_job_resource<O: JsonSchema + Serialize, E: JsonSchema + Serialize>(
+        &mut self,
+        job_path: &ApiPath,
+    ) -> String {
+        let path = job_path.to_string();
+        let job_schema = self.register_schema::<Job<O>>().into_object();
+        if let Some(op) = self.generator.operation_mut(&path, &http::Method::GET) {
+            let registered = match op.responses.responses.get("200") {
+                Some(RefOr::Object(response)) => response
+                    .content
+                    .values()
+                    .next()
+                    .and_then(|media| media.schema.clone()),
+                _ => None,
+            };
+            assert!(
+                registered.as_ref() == Some(&job_schema),
+                "{} is already registered with another job result type than {}",
+                path,
+                std::any::type_name::<O>()
+            );
+            return op.operation_id.clone().unwrap_or_default();
+        }
+        let document_name = job_path
+            .ids
+            .last()
+            .map(|id| id.document.to_camel_case())
+            .unwrap_or_default();
+        let operation_id = format!("fetch{document_name}");
+
+        let mut resps = Responses::default();
+        let status = "200".to_owned();
+        let resp = self.create_response::<Job<O>>(document_name);
+        resps.responses.insert(status, resp.into());
+        self.add_error_responses::<E>(&mut resps);
+
+        let mut parameters: Vec<RefOr<Parameter>> = vec![];
+        self.add_path_params(job_path.clone(), &mut parameters);
+
+        self.generator.add_operation(OperationInfo {
+            path,
+            method: http::Method::GET,
+            operation: Operation {
+                operation_id: Some(operation_id.clone()),
+                summary: Some("Poll the status of a long-running operation".to_owned()),
+                responses: resps,
+                parameters,
+                ..Operation::default()
+            },
+        });
+        operation_id
+    }
+
+    fn create_location_header(&mut self, job_path: &ApiPath) -> Header {
+        Header {
+            description: Some(format!("The url of the job, {job_path}")),
+            required: true,
+            deprecated: false,
+            allow_empty_value: false,
+            value: ParameterValue::Schema {
+                style: None,
+                explode: None,
+                allow_reserved: false,
+                schema: self
+                    .generator
+                    .schema_generator
+                    .subschema_for::<String>()
+                    .into(),
+                example: None,
+                examples: None,
+            },
+            extensions: Map::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ApiId, ApiPath, Oas3Builder};
+    use schemars::JsonSchema;
+    use serde::Serialize;
+    use serde_json::json;
+
+    #[derive(Serialize, JsonSchema)]
+    pub struct TestReport {
+        pub rows: u64,
+    }
+
+    #[test]
+    fn test_async_operation() {
+        let export_path = ApiPath::new(
+            Some("api".to_owned()),
+            vec![ApiId::new("events", "{eid}")],
+            Some("export".to_owned()),
+        );
+        let job_path = ApiPath::new(
+            Some("api".to_owned()),
+            vec![ApiId::new("jobs", "{jobId}")],
+            None,
+        );
+        let mut oasb = Oas3Builder::default();
+        oasb.async_operation::<(), TestReport, String>(
+            &export_path,
+            http::Method::POST,
+            &job_path,
+            "Events".to_owned(),
+            "export",
+            None,
+        );
+        let import_path = ApiPath::new(Some("api".to_owned()), vec![], Some("import".to_owned()));
+        oasb.async_operation::<(), TestReport, String>(
+            &import_path,
+            http::Method::POST,
+            &job_path,
+            "Events".to_owned(),
+            "import",
+            None,
+        );
+        let got = serde_json::to_value(oasb.build("1.0.0".to_owned())).unwrap();
+        let _openapi_json: openapiv3::OpenAPI = serde_json::from_value(got.clone()).unwrap();
+
+        let accepted = &got["paths"]["/api/events/{eid}/export"]["post"]["responses"]["202"];
+        assert_eq!(
+            json!({"$ref": "#/components/schemas/Job_for_TestReport"}),
+            accepted["content"]["application/json; charset=utf-8"]["schema"]
+        );
+        assert_eq!(json!(true), accepted["headers"]["Location"]["required"]);
+        assert_eq!(
+            json!({
+                "operationId": "fetchJobs",
+                "parameters": {"jobId": "$response.body#/id"},
+                "description": "The status of the started job."
+            }),
+            accepted["links"]["FetchJobs"]
+        );
+
+        let job = &got["paths"]["/api/jobs/{jobId}"]["get"];
+        assert_eq!(json!("fetchJobs"), job["operationId"]);
+        assert_eq!(json!("jobId"), job["parameters"][0]["name"]);
+        assert!(
+            got["paths"]["/api/import"]["post"]["responses"]["202"]["links"]["FetchJobs"]
+                .is_object()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "is already registered with another job result type")]
+    fn test_job_result_mismatch() {
+        let job_path = ApiPath::new(
+            Some("api".to_owned()),
+            vec![ApiId::new("jobs", "{jobId}")],
+            None,
+        );
+        let export_path = ApiPath::new(Some("api".to_owned()), vec![], Some("export".to_owned()));
+        let import_path = ApiPath::new(Some("api".to_owned()), vec![], Some("import".to_owned()));
+        let mut oasb = Oas3Builder::default();
+        oasb.async_operation::<(), TestReport, String>(
+            &export_path,
+            http::Method::POST,
+            &job_path,
+            "Events".to_owned(),
+            "export",
+            None,
+        );
+        oasb.async_operation::<(), u64, String>(
+            &import_path,
+            http::Method::POST,
+            &job_path,
+            "Events".to_owned(),
+            "import",
+            None,
+        );
+    }
+}
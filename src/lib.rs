@@ -2,15 +2,37 @@
 #![warn(clippy::pedantic)]
 #![allow(non_fmt_panic)]
 
+mod action;
 mod any;
 mod apipath;
+mod batch;
+mod components;
+mod constraints;
 mod create;
 mod delete;
+mod discriminator;
+mod examples;
 mod fetch;
+mod hurl;
+mod job;
 mod list;
+#[cfg(feature = "mock-server")]
+mod mockserver;
+mod naming;
+mod pact;
+mod patch;
 mod queryparam;
+mod readwrite;
 mod replace;
+mod resource;
+mod runner;
+mod scope;
+mod singleton;
+mod smoketests;
+mod synthesis;
+mod transform;
 mod update;
+mod validation;
 
 pub mod jsonobject;
 mod oasgen;
@@ -19,12 +41,29 @@ mod generator;
 pub mod xtests;
 
 pub use any::*;
+pub use batch::*;
 pub use create::*;
 pub use delete::*;
+pub use examples::*;
 pub use fetch::*;
+pub use hurl::*;
+pub use job::*;
 pub use list::*;
+#[cfg(feature = "mock-server")]
+pub use mockserver::*;
+pub use naming::*;
+pub use pact::*;
+pub use patch::*;
+pub use readwrite::*;
 pub use replace::*;
+pub use resource::*;
+pub use runner::*;
+pub use scope::*;
+pub use smoketests::*;
+pub use synthesis::*;
+pub use transform::*;
 pub use update::*;
+pub use validation::*;
 
 pub use apipath::*;
 pub use oasgen::*;
@@ -0,0 +1,757 @@
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode, Uri};
+use axum::response::{IntoResponse, Response as AxumResponse};
+use axum::Router;
+use schemars::schema::{InstanceType, SchemaObject, SingleOrVec};
+use serde_json::{json, Value};
+
+use crate::examples::media_example;
+use crate::generator::{
+    Map, OpenApi, OpenApiGenerator, Operation, Parameter, ParameterValue, RefOr, RequestBody,
+    Response, SecurityScheme, SecuritySchemeData,
+};
+use crate::synthesis::ExampleGenerator;
+use crate::validation::SchemaValidator;
+
+/// Serves a spec built by `Oas3Builder::build`, e.g. for frontend work before the
+/// backend exists. Requires the `mock-server` feature.
+///
+/// Requests are matched to the operations by path and method; parameters and JSON
+/// request bodies are validated against their schema, and secured operations
+/// require the credentials of one of their security requirements, e.g. the header
+/// of an `apiKey` scheme or an `Authorization` header. Valid requests get the
+/// lowest documented 2xx status with its example, or with data synthesised from
+/// the schema; invalid ones get 400, 401, 404 or 405 with `{"errors": [..]}`.
+/// ```no_run
+/// # use oas_gen::{MockServer, Oas3Builder};
+/// # let openapi = Oas3Builder::default().build("1.0.0".to_owned());
+/// let address = MockServer::new(openapi).seed(7).spawn("127.0.0.1:8080").unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct MockServer {
+    openapi: OpenApi,
+    definitions: Map<String, SchemaObject>,
+    routes: Vec<MockRoute>,
+    seed: u64,
+}
+
+#[derive(Debug, Clone)]
+struct MockRoute {
+    segments: Vec<String>,
+    method: http::Method,
+    operation: Operation,
+}
+
+/// The status, headers and JSON body `MockServer` answers a request with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockResponse {
+    pub status: u16,
+    pub headers: Map<String, String>,
+    pub body: Option<Value>,
+}
+
+impl MockServer {
+    #[must_use]
+    pub fn new(openapi: OpenApi) -> Self {
+        let definitions = openapi
+            .components
+            .as_ref()
+            .map(|components| components.schemas.clone())
+            .unwrap_or_default();
+        let mut routes = vec![];
+        for (path, path_item) in &openapi.paths {
+            for (method, operation) in OpenApiGenerator::path_item_operation_refs(path_item) {
+                routes.push(MockRoute {
+                    segments: path.split('/').map(ToOwned::to_owned).collect(),
+                    method,
+                    operation: operation.clone(),
+                });
+            }
+        }
+        // literal segments win over placeholders, e.g. `/events/batch` over `/events/{eid}`
+        routes.sort_by_key(|route| {
+            let placeholders = route.segments.iter().filter(|s| s.starts_with('{'));
+            placeholders.count()
+        });
+        MockServer {
+            openapi,
+            definitions,
+            routes,
+            seed: 0,
+        }
+    }
+
+    /// The seed of the data synthesised for responses without an example, defaults to 0.
+    #[must_use]
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// The answer to a request, the names of its `headers` in any case.
+    #[must_use]
+    pub fn respond(
+        &self,
+        method: &http::Method,
+        path: &str,
+        query: Option<&str>,
+        headers: &[(&str, &str)],
+        body: &[u8],
+    ) -> MockResponse {
+        let segments: Vec<&str> = path.split('/').collect();
+        let matching: Vec<_> = self
+            .routes
+            .iter()
+            .filter_map(|route| Some((route, match_path(&route.segments, &segments)?)))
+            .collect();
+        if matching.is_empty() {
+            return errors(404, &[format!("No operation is defined for {path}")]);
+        }
+        let Some((route, path_params)) = matching.iter().find(|(route, _)| route.method == method)
+        else {
+            let mut allowed: Vec<&str> = vec![];
+            for (route, _) in &matching {
+                if !allowed.contains(&route.method.as_str()) {
+                    allowed.push(route.method.as_str());
+                }
+            }
+            let mut response = errors(
+                405,
+                &[format!("No operation is defined for {method} {path}")],
+            );
+            response
+                .headers
+                .insert("Allow".to_owned(), allowed.join(", "));
+            return response;
+        };
+
+        let op = &route.operation;
+        let query = parse_query(query);
+        let cookies = parse_cookies(headers);
+        let requirements = op.security.as_ref().unwrap_or(&self.openapi.security);
+        if !requirements.is_empty()
+            && !requirements
+                .iter()
+                .any(|requirement| self.satisfies(requirement, &query, headers, &cookies))
+        {
+            return errors(401, &["The operation requires authorization".to_owned()]);
+        }
+
+        let problems = self.validate_request(op, path_params, &query, headers, &cookies, body);
+        if !problems.is_empty() {
+            return errors(400, &problems);
+        }
+
+        let Some((status, response)) = op
+            .responses
+            .responses
+            .iter()
+            .filter(|(status, _)| status.starts_with('2'))
+            .find_map(|(status, response)| {
+                Some((status.parse().ok()?, self.resolve_response(response)?))
+            })
+        else {
+            return MockResponse {
+                status: 204,
+                headers: Map::default(),
+                body: None,
+            };
+        };
+        let body = response.content.values().next().map(|media| {
+            media_example(media).unwrap_or_else(|| match &media.schema {
+                Some(schema) => {
+                    ExampleGenerator::new(&self.definitions, self.seed).generate(schema)
+                }
+                None => Value::Null,
+            })
+        });
+        MockResponse {
+            status,
+            headers: Map::default(),
+            body,
+        }
+    }
+
+    /// The problems of the parameters and the body of a request to `op`.
+    fn validate_request(
+        &self,
+        op: &Operation,
+        path_params: &Map<String, String>,
+        query: &[(String, String)],
+        headers: &[(&str, &str)],
+        cookies: &[(&str, &str)],
+        body: &[u8],
+    ) -> Vec<String> {
+        let validator = SchemaValidator::new(&self.definitions);
+        let mut problems = vec![];
+        for parameter in op
+            .parameters
+            .iter()
+            .filter_map(|p| self.resolve_parameter(p))
+        {
+            // every value of a repeated, i.e. exploded, query parameter
+            let raw: Vec<String> = match parameter.location.as_str() {
+                "path" => path_params
+                    .get(&parameter.name)
+                    .cloned()
+                    .into_iter()
+                    .collect(),
+                "query" => query
+                    .iter()
+                    .filter(|(name, _)| *name == parameter.name)
+                    .map(|(_, value)| value.clone())
+                    .collect(),
+                "header" => headers
+                    .iter()
+                    .filter(|(name, _)| name.eq_ignore_ascii_case(&parameter.name))
+                    .map(|(_, value)| (*value).to_owned())
+                    .collect(),
+                "cookie" => cookies
+                    .iter()
+                    .filter(|(name, _)| *name == parameter.name)
+                    .map(|(_, value)| (*value).to_owned())
+                    .collect(),
+                _ => continue,
+            };
+            let ParameterValue::Schema { schema, .. } = &parameter.value else {
+                continue;
+            };
+            match raw.as_slice() {
+                [_, ..] => {
+                    let value = self.coerce(&raw, schema);
+                    if let Err(errors) = validator.validate(&value, schema) {
+                        for error in errors {
+                            problems.push(format!("parameter `{}` {}", parameter.name, error));
+                        }
+                    }
+                }
+                [] if parameter.required => {
+                    problems.push(format!("parameter `{}` is missing", parameter.name));
+                }
+                [] => {}
+            }
+        }
+        if let Some(request_body) = op
+            .request_body
+            .as_ref()
+            .and_then(|b| self.resolve_request_body(b))
+        {
+            let schema = request_body
+                .content
+                .values()
+                .find_map(|media| media.schema.as_ref());
+            if body.is_empty() {
+                if request_body.required {
+                    problems.push("the request body is missing".to_owned());
+                }
+            } else {
+                match serde_json::from_slice::<Value>(body) {
+                    Ok(value) => {
+                        if let Some(Err(errors)) =
+                            schema.map(|schema| validator.validate(&value, schema))
+                        {
+                            for error in errors {
+                                problems.push(format!("body {error}"));
+                            }
+                        }
+                    }
+                    Err(e) => problems.push(format!("the request body is not JSON: {e}")),
+                }
+            }
+        }
+        problems
+    }
+
+    /// Whether a request has the credentials of every scheme of `requirement`:
+    /// the header, query parameter or cookie named by an `apiKey` scheme, else an
+    /// `Authorization` header. An empty requirement makes the security optional.
+    fn satisfies(
+        &self,
+        requirement: &Map<String, Vec<String>>,
+        query: &[(String, String)],
+        headers: &[(&str, &str)],
+        cookies: &[(&str, &str)],
+    ) -> bool {
+        let has_header = |header: &str| {
+            headers
+                .iter()
+                .any(|(name, _)| name.eq_ignore_ascii_case(header))
+        };
+        requirement.keys().all(|scheme| {
+            let scheme = self
+                .openapi
+                .components
+                .as_ref()
+                .and_then(|components| components.security_schemes.get(scheme));
+            match scheme {
+                Some(RefOr::Object(SecurityScheme {
+                    data: SecuritySchemeData::ApiKey { name, location },
+                    ..
+                })) => match location.as_str() {
+                    "query" => query.iter().any(|(key, _)| key == name),
+                    "cookie" => cookies.iter().any(|(key, _)| key == name),
+                    _ => has_header(name),
+                },
+                _ => has_header("authorization"),
+            }
+        })
+    }
+
+    /// An axum router answering every request, to nest into an application.
+    pub fn router(self) -> Router {
+        Router::new().fallback(handle).with_state(Arc::new(self))
+    }
+
+    /// Serves on `listener` until the server fails.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if serving fails.
+    pub async fn serve(self, listener: tokio::net::TcpListener) -> io::Result<()> {
+        axum::serve(listener, self.router()).await
+    }
+
+    /// Serves on `address`, e.g. `127.0.0.1:0`, from a background thread with its
+    /// own runtime, returns the bound address.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if `address` can not be bound.
+    pub fn spawn(self, address: &str) -> io::Result<SocketAddr> {
+        let listener = std::net::TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        let address = listener.local_addr()?;
+        let runtime = tokio::runtime::Runtime::new()?;
+        std::thread::spawn(move || {
+            runtime.block_on(async move {
+                let listener = tokio::net::TcpListener::from_std(listener)?;
+                self.serve(listener).await
+            })
+        });
+        Ok(address)
+    }
+
+    fn resolve_parameter<'a>(&'a self, parameter: &'a RefOr<Parameter>) -> Option<&'a Parameter> {
+        match parameter {
+            RefOr::Object(parameter) => Some(parameter),
+            RefOr::Ref(reference) => {
+                let components = self.openapi.components.as_ref()?;
+                match components
+                    .parameters
+                    .get(component_name(&reference.reference))?
+                {
+                    RefOr::Object(parameter) => Some(parameter),
+                    RefOr::Ref(_) => None,
+                }
+            }
+        }
+    }
+
+    fn resolve_request_body<'a>(
+        &'a self,
+        request_body: &'a RefOr<RequestBody>,
+    ) -> Option<&'a RequestBody> {
+        match request_body {
+            RefOr::Object(request_body) => Some(request_body),
+            RefOr::Ref(reference) => {
+                let components = self.openapi.components.as_ref()?;
+                match components
+                    .request_bodies
+                    .get(component_name(&reference.reference))?
+                {
+                    RefOr::Object(request_body) => Some(request_body),
+                    RefOr::Ref(_) => None,
+                }
+            }
+        }
+    }
+
+    fn resolve_response<'a>(&'a self, response: &'a RefOr<Response>) -> Option<&'a Response> {
+        match response {
+            RefOr::Object(response) => Some(response),
+            RefOr::Ref(reference) => {
+                let components = self.openapi.components.as_ref()?;
+                match components
+                    .responses
+                    .get(component_name(&reference.reference))?
+                {
+                    RefOr::Object(response) => Some(response),
+                    RefOr::Ref(_) => None,
+                }
+            }
+        }
+    }
+
+    /// The value of the raw values of a parameter, typed by `schema` so it can be
+    /// validated: the items of arrays are the values, split at commas, other types
+    /// take the first value.
+    fn coerce(&self, values: &[String], schema: &SchemaObject) -> Value {
+        let raw = values.first().map_or("", String::as_str);
+        let schema = match &schema.reference {
+            Some(reference) => match self.definitions.get(component_name(reference)) {
+                Some(schema) => schema,
+                None => return Value::String(raw.to_owned()),
+            },
+            None => schema,
+        };
+        let instance_type = match &schema.instance_type {
+            Some(SingleOrVec::Single(it)) => Some(**it),
+            Some(SingleOrVec::Vec(its)) => its.first().copied(),
+            None => None,
+        };
+        let parsed = match instance_type {
+            Some(InstanceType::Integer) => raw.parse::<i64>().ok().map(Value::from),
+            Some(InstanceType::Number) => raw.parse::<f64>().ok().map(Value::from),
+            Some(InstanceType::Boolean) => raw.parse::<bool>().ok().map(Value::from),
+            Some(InstanceType::Array) => {
+                let items = schema.array.as_ref().and_then(|array| match &array.items {
+                    Some(SingleOrVec::Single(items)) => Some(items.clone().into_object()),
+                    _ => None,
+                });
+                let values = values
+                    .iter()
+                    .flat_map(|value| value.split(','))
+                    .map(|item| match &items {
+                        Some(items) => self.coerce(&[item.to_owned()], items),
+                        None => Value::String(item.to_owned()),
+                    })
+                    .collect();
+                Some(Value::Array(values))
+            }
+            _ => None,
+        };
+        parsed.unwrap_or_else(|| Value::String(raw.to_owned()))
+    }
+}
+
+async fn handle(
+    State(server): State<Arc<MockServer>>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    body: Bytes,
+) -> AxumResponse {
+    let method = http::Method::from_bytes(method.as_str().as_bytes()).unwrap_or_default();
+    let headers: Vec<(&str, &str)> = headers
+        .iter()
+        .filter_map(|(name, value)| Some((name.as_str(), value.to_str().ok()?)))
+        .collect();
+    let response = server.respond(&method, uri.path(), uri.query(), &headers, &body);
+    let status = StatusCode::from_u16(response.status).unwrap_or(StatusCode::OK);
+    let mut answer = match response.body {
+        Some(body) => (status, axum::Json(body)).into_response(),
+        None => status.into_response(),
+    };
+    for (name, value) in response.headers {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(&value),
+        ) {
+            answer.headers_mut().insert(name, value);
+        }
+    }
+    answer
+}
+
+/// The path params if `segments` of a path template match the segments of a path.
+fn match_path(template: &[String], segments: &[&str]) -> Option<Map<String, String>> {
+    if template.len() != segments.len() {
+        return None;
+    }
+    let mut params = Map::default();
+    for (expected, actual) in template.iter().zip(segments) {
+        match expected.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+            Some(name) if !actual.is_empty() => {
+                params.insert(name.to_owned(), decode(actual));
+            }
+            None if expected == actual => {}
+            _ => return None,
+        }
+    }
+    Some(params)
+}
+
+fn component_name(reference: &str) -> &str {
+    reference.rsplit('/').next().unwrap_or_default()
+}
+
+fn errors(status: u16, errors: &[String]) -> MockResponse {
+    MockResponse {
+        status,
+        headers: Map::default(),
+        body: Some(json!({ "errors": errors })),
+    }
+}
+
+/// Percent decodes a query component, `+` being a space.
+/// The decoded name and value pairs of a query string, in order.
+fn parse_query(query: Option<&str>) -> Vec<(String, String)> {
+    query
+        .map(|query| {
+            query
+                .split('&')
+                .filter(|pair| !pair.is_empty())
+                .map(|pair| {
+                    let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+                    (decode_query(name), decode_query(value))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// The name and value pairs of the `Cookie` headers.
+fn parse_cookies<'a>(headers: &[(&str, &'a str)]) -> Vec<(&'a str, &'a str)> {
+    headers
+        .iter()
+        .filter(|(name, _)| name.eq_ignore_ascii_case("cookie"))
+        .flat_map(|(_, cookie)| cookie.split(';'))
+        .filter_map(|pair| {
+            let (name, value) = pair.split_once('=')?;
+            Some((name.trim(), value.trim()))
+        })
+        .collect()
+}
+
+fn decode_query(text: &str) -> String {
+    decode(&text.replace('+', "%20"))
+}
+
+/// Percent decodes a path segment.
+fn decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' if i + 2 < bytes.len() => {
+                let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+                match hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                    Some(byte) => {
+                        decoded.push(byte);
+                        i += 3;
+                        continue;
+                    }
+                    None => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, decode_query, MockResponse, MockServer};
+    use crate::generator::{Map, Parameter, RefOr};
+    use crate::xtests::{BodyAssertion, Test};
+    use crate::{ApiId, ApiPath, Oas3Builder, QueryParamBuilder, TestRunner};
+    use schemars::JsonSchema;
+    use serde::Serialize;
+    use serde_json::json;
+
+    #[derive(Serialize, JsonSchema)]
+    pub struct TestEvent {
+        pub title: String,
+        pub seats: u32,
+    }
+
+    fn spec() -> crate::generator::OpenApi {
+        let events_path = ApiPath::new(Some("api".to_owned()), vec![], Some("events".to_owned()));
+        let event_path = ApiPath::new(
+            Some("api".to_owned()),
+            vec![ApiId::new("events", "{eid}")],
+            None,
+        );
+        let create = Test::new("Create an event.")
+            .json_body(&json!({"title": "Hackaton", "seats": 20}))
+            .expect_status(201)
+            .expect_body(BodyAssertion::MatchesSchema)
+            .build();
+        let invalid = Test::new("Create an invalid event.")
+            .json_body(&json!({"title": "Hackaton", "seats": -1}))
+            .expect_status(400)
+            .build();
+        let fetch = Test::new("Fetch an event.")
+            .param("eid", "e1")
+            .expect_status(201)
+            .expect_body(BodyAssertion::Exact(
+                json!({"title": "Hackaton", "seats": 20}),
+            ))
+            .build();
+        let mut oasb = Oas3Builder::default();
+        oasb.create_with_tests::<TestEvent, TestEvent, String>(
+            &events_path,
+            "Events".to_owned(),
+            None,
+            &[create, invalid],
+        );
+        oasb.fetch_with_tests::<TestEvent, String>(
+            &event_path,
+            "Events".to_owned(),
+            None,
+            &[fetch],
+        );
+        oasb.response_example(
+            &event_path,
+            &http::Method::GET,
+            201,
+            &json!({"title": "Hackaton", "seats": 20}),
+        );
+        oasb.build("1.0.0".to_owned())
+    }
+
+    #[test]
+    fn test_respond() {
+        let server = MockServer::new(spec());
+        let missing = server.respond(&http::Method::GET, "/api/venues", None, &[], b"");
+        assert_eq!(404, missing.status);
+        let wrong_method = server.respond(&http::Method::PATCH, "/api/events/e1", None, &[], b"");
+        assert_eq!(405, wrong_method.status);
+        assert_eq!(
+            Some("GET"),
+            wrong_method.headers.get("Allow").map(String::as_str)
+        );
+        let invalid = server.respond(
+            &http::Method::POST,
+            "/api/events",
+            None,
+            &[],
+            br#"{"title": 1}"#,
+        );
+        assert_eq!(
+            MockResponse {
+                status: 400,
+                headers: Map::default(),
+                body: Some(json!({"errors": [
+                    "body /: missing required property `seats`",
+                    "body /title: expected [String], found 1"
+                ]})),
+            },
+            invalid
+        );
+        let created = server.respond(
+            &http::Method::POST,
+            "/api/events",
+            None,
+            &[],
+            br#"{"title": "Hackaton", "seats": 20}"#,
+        );
+        assert_eq!(201, created.status);
+        let body = created.body.unwrap();
+        assert!(body["title"].is_string() && body["seats"].is_u64());
+    }
+
+    #[test]
+    fn test_parameters() {
+        let ids = QueryParamBuilder::new::<Vec<u32>>("ids".to_owned(), None).required(true);
+        let events_path = ApiPath::with_queries(
+            Some("api".to_owned()),
+            vec![],
+            Some("events".to_owned()),
+            vec![ids],
+        );
+        let mut oasb = Oas3Builder::default();
+        oasb.list::<Vec<TestEvent>, String>(&events_path, "Events".to_owned(), None);
+        for (name, location) in [("X-Tenant", "header"), ("session", "cookie")] {
+            let param = QueryParamBuilder::new::<u32>(name.to_owned(), None).build();
+            let param = oasb.parameter_component(
+                name,
+                Parameter {
+                    location: location.to_owned(),
+                    required: true,
+                    ..param
+                },
+            );
+            oasb.use_parameter(&events_path, &http::Method::GET, param);
+        }
+        let server = MockServer::new(oasb.build("1.0.0".to_owned()));
+        let list = |query: &str, headers: &[(&str, &str)]| {
+            let response =
+                server.respond(&http::Method::GET, "/api/events", Some(query), headers, b"");
+            (response.status == 400).then_some(response.body).flatten()
+        };
+
+        let headers = [("x-tenant", "7"), ("Cookie", "theme=dark; session=3")];
+        assert_eq!(None, list("ids=1&ids=2", &headers));
+        assert_eq!(None, list("ids=1,2", &headers));
+        assert_eq!(
+            Some(json!({"errors": ["parameter `ids` /1: expected [Integer], found \"x\""]})),
+            list("ids=1&ids=x", &headers)
+        );
+        assert_eq!(
+            Some(json!({"errors": [
+                "parameter `X-Tenant` is missing",
+                "parameter `session` /: expected [Integer], found \"abc\""
+            ]})),
+            list("ids=1", &[("Cookie", "session=abc")])
+        );
+    }
+
+    #[test]
+    fn test_security() {
+        let mut openapi = spec();
+        let components = openapi.components.as_mut().unwrap();
+        components.security_schemes.insert(
+            "bearerAuth".to_owned(),
+            RefOr::Object(Oas3Builder::create_bearer_scheme()),
+        );
+        components.security_schemes.insert(
+            "apiKey".to_owned(),
+            RefOr::Object(Oas3Builder::create_apikey_scheme("X-API-KEY".to_owned())),
+        );
+        let requirement = |scheme: &str| {
+            let mut requirement = Map::default();
+            requirement.insert(scheme.to_owned(), vec![]);
+            requirement
+        };
+        openapi.security = vec![requirement("bearerAuth")];
+        let path_item = openapi.paths.get_mut("/api/events/{eid}").unwrap();
+        path_item.get.as_mut().unwrap().security = Some(vec![requirement("apiKey")]);
+        let server = MockServer::new(openapi.clone());
+        let fetch = |headers: &[(&str, &str)]| {
+            server
+                .respond(&http::Method::GET, "/api/events/e1", None, headers, b"")
+                .status
+        };
+        assert_eq!(401, fetch(&[]));
+        assert_eq!(401, fetch(&[("Authorization", "Bearer abc")]));
+        assert_eq!(201, fetch(&[("x-api-key", "abc")]));
+        let body = br#"{"title": "Hackaton", "seats": 20}"#;
+        let create = |server: &MockServer, headers: &[(&str, &str)]| {
+            server
+                .respond(&http::Method::POST, "/api/events", None, headers, body)
+                .status
+        };
+        assert_eq!(401, create(&server, &[]));
+        assert_eq!(201, create(&server, &[("Authorization", "Bearer abc")]));
+
+        // an empty requirement makes the security optional
+        openapi.security.push(Map::default());
+        let server = MockServer::new(openapi);
+        assert_eq!(201, create(&server, &[]));
+    }
+
+    #[test]
+    fn test_decode() {
+        assert_eq!("a+b c", decode("a+b%20c"));
+        assert_eq!("a b+c", decode_query("a+b%2Bc"));
+    }
+
+    #[test]
+    fn test_serve() {
+        let address = MockServer::new(spec()).spawn("127.0.0.1:0").unwrap();
+        let report = TestRunner::new(&format!("http://{address}"))
+            .run(&spec())
+            .unwrap();
+        report.assert_success();
+        assert_eq!(3, report.passed());
+    }
+}
@@ -0,0 +1,547 @@
+use std::collections::BTreeSet;
+
+use heck::CamelCase;
+use schemars::gen::SchemaGenerator;
+use schemars::schema::{Schema, SchemaObject};
+use schemars::visit::{visit_schema_object, Visitor};
+use schemars::JsonSchema;
+
+use crate::generator::{Map, OpenApi, OpenApiGenerator};
+use crate::oasgen::Oas3Builder;
+
+/// How the names of component schemas are derived from the names generated by schemars.
+#[derive(Debug, Default, Clone, Copy)]
+pub enum SchemaNaming {
+    /// The generated names, e.g. `CollectionWrapper_for_TestEvent`.
+    #[default]
+    Generated,
+    /// Generic arguments are moved in front of the type, e.g. `TestEventCollectionWrapper`.
+    FlattenGenerics,
+    /// A custom function of the generated name.
+    Custom(fn(&str) -> String),
+    /// The generated names of registered types prefixed with their module, e.g.
+    /// `EventsStatus` for `events::Status`, and their generic arguments too, e.g.
+    /// `ApiWrapper_for_EventsStatus` for `api::Wrapper<events::Status>`. Registered types of the same name in
+    /// different modules no longer collide; set it before registering any operation.
+    /// Other types only reached through the properties of registered ones keep
+    /// their generated name, as their module is not known.
+    ModuleQualified,
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SchemaNames {
+    naming: SchemaNaming,
+    renames: Map<String, String>,
+    // the module qualified names of the definitions of registered types
+    qualified: Map<String, String>,
+    // the names of the merge patch definitions, which schemars does not know of
+    pub(crate) patches: BTreeSet<String>,
+}
+
+impl Oas3Builder {
+    /// Sets how component schemas are named, applied by `build`.
+    pub fn schema_naming(&mut self, naming: SchemaNaming) -> &mut Self {
+        self.schema_names.naming = naming;
+        self
+    }
+
+    /// Renames the component schema generated as `from` to `to`, taking precedence
+    /// over the `SchemaNaming`.
+    pub fn rename_schema(&mut self, from: &str, to: &str) -> &mut Self {
+        self.schema_names
+            .renames
+            .insert(from.to_owned(), to.to_owned());
+        self
+    }
+
+    /// `subschema_for`, recording the module of `T` for `SchemaNaming::ModuleQualified`,
+    /// under which `T` may share its name with a type of another module.
+    ///
+    /// # Panics
+    ///
+    /// Will panic on a name collision, see `subschema_for`, or if a definition of `T`
+    /// is named like a merge patch
+    pub(crate) fn register_schema<T: JsonSchema>(&mut self) -> Schema {
+        let generator = &mut self.generator.schema_generator;
+        let registered: BTreeSet<String> = generator.definitions().keys().cloned().collect();
+        let schema = generator.subschema_for::<T>();
+        let prefix = &generator.settings().definitions_path;
+        let name = schema
+            .clone()
+            .into_object()
+            .reference
+            .and_then(|reference| {
+                reference
+                    .strip_prefix(prefix.as_str())
+                    .map(ToOwned::to_owned)
+            });
+        // module qualified names tell apart what schemars numbered: `T` and the
+        // definitions of its generic arguments
+        let mut qualified = Map::default();
+        if let SchemaNaming::ModuleQualified = self.schema_names.naming {
+            let type_name = std::any::type_name::<T>();
+            let arguments = qualified_arguments(type_name);
+            for added in generator.definitions().keys() {
+                let base = added.trim_end_matches(|c: char| c.is_ascii_digit());
+                if let Some(Some(argument)) = arguments.get(base) {
+                    if !registered.contains(added) {
+                        qualified.insert(added.clone(), argument.clone());
+                    }
+                }
+            }
+            if let Some(name) = &name {
+                qualified.insert(name.clone(), module_qualified(type_name, &T::schema_name()));
+            }
+        }
+        let allowed = qualified.keys().cloned().collect();
+        check_names::<T>(generator, &registered, &allowed);
+        if !self.schema_names.patches.is_empty() {
+            // schemars reuses a definition of the same name, see `merge_patch_schema_for`
+            let mut own = SchemaGenerator::new(generator.settings().clone());
+            own.subschema_for::<T>();
+            for name in own.definitions().keys() {
+                assert!(
+                    !self.schema_names.patches.contains(name),
+                    "The schema `{}` is named like a merge patch, rename it with #[schemars(rename = \"..\")]",
+                    name
+                );
+            }
+        }
+        for (name, qualified) in qualified {
+            // two types still qualified alike keep the names schemars numbered
+            let taken = self
+                .schema_names
+                .qualified
+                .iter()
+                .any(|(other, q)| *other != name && *q == qualified);
+            if !taken {
+                self.schema_names.qualified.entry(name).or_insert(qualified);
+            }
+        }
+        schema
+    }
+}
+
+/// `subschema_for::<T>` which checks that none of the definitions of `T`
+/// share its name with a different schema already registered in `generator`.
+///
+/// schemars keys definitions by type, but names a type sharing its name with
+/// another one, e.g. `events::Status` and `tickets::Status`, `Status2`.
+///
+/// # Panics
+///
+/// Will panic on a name collision, rename one of the types with `#[schemars(rename = "..")]`
+/// or use `SchemaNaming::ModuleQualified`
+pub(crate) fn subschema_for<T: JsonSchema>(generator: &mut SchemaGenerator) -> Schema {
+    let registered: BTreeSet<String> = generator.definitions().keys().cloned().collect();
+    let schema = generator.subschema_for::<T>();
+    check_names::<T>(generator, &registered, &BTreeSet::new());
+    schema
+}
+
+/// Checks the definitions added to `generator` since `registered` for names
+/// numbered by schemars to avoid a collision, except `allowed`.
+///
+/// # Panics
+///
+/// Will panic on a name collision
+fn check_names<T: JsonSchema>(
+    generator: &SchemaGenerator,
+    registered: &BTreeSet<String>,
+    allowed: &BTreeSet<String>,
+) {
+    let definitions = generator.definitions();
+    let numbered = definitions
+        .keys()
+        .filter(|name| !registered.contains(*name) && !allowed.contains(*name))
+        .any(|name| {
+            let base = name.trim_end_matches(|c: char| c.is_ascii_digit());
+            base.len() < name.len() && definitions.contains_key(base)
+        });
+    if !numbered {
+        return;
+    }
+    // a type may be named like `Point2` on its own, the names `T` gets on its own tell
+    let mut own = SchemaGenerator::new(generator.settings().clone());
+    own.subschema_for::<T>();
+    // the names the allowed definitions have on their own
+    let allowed: BTreeSet<&str> = allowed
+        .iter()
+        .map(|name| name.trim_end_matches(|c: char| c.is_ascii_digit()))
+        .collect();
+    for (name, schema) in own.definitions() {
+        if allowed.contains(name.as_str()) {
+            continue;
+        }
+        if let Some(registered) = registered.get(name).and_then(|name| definitions.get(name)) {
+            assert!(
+                registered == schema,
+                "Two different schemas are named `{}`, rename one with #[schemars(rename = \"..\")] or use SchemaNaming::ModuleQualified",
+                name
+            );
+        }
+    }
+}
+
+/// `name` prefixed with the module of `type_name`, `EventsStatus` for `Status`
+/// and `my_crate::events::Status`. The generic arguments in `name` are qualified
+/// too, except those of the standard library.
+fn module_qualified(type_name: &str, name: &str) -> String {
+    let path = type_name.split('<').next().unwrap_or_default();
+    let qualified_arguments = qualified_arguments(type_name);
+    let name = name
+        .split('_')
+        .enumerate()
+        .map(|(i, token)| match qualified_arguments.get(token) {
+            Some(Some(qualified)) if i > 0 => qualified.as_str(),
+            _ => token,
+        })
+        .collect::<Vec<_>>()
+        .join("_");
+    let mut segments = path.rsplit("::").skip(1);
+    match segments.next() {
+        Some(module) => format!("{}{name}", module.to_camel_case()),
+        None => name,
+    }
+}
+
+/// The module qualified names of the generic arguments of `type_name` by their
+/// own names, `None` for a name shared by arguments of different modules.
+fn qualified_arguments(type_name: &str) -> Map<&str, Option<String>> {
+    let arguments = type_name
+        .split_once('<')
+        .map_or("", |(_, arguments)| arguments);
+    let mut qualified_arguments: Map<&str, Option<String>> = Map::default();
+    for argument in arguments
+        .split(|c: char| "<>,[]();&".contains(c) || c.is_whitespace())
+        .filter(|argument| argument.contains("::"))
+        .filter(|argument| {
+            !["std::", "core::", "alloc::"]
+                .iter()
+                .any(|s| argument.starts_with(s))
+        })
+    {
+        let ident = argument.rsplit("::").next().unwrap_or_default();
+        let qualified = module_qualified(argument, ident);
+        // the same name from two modules can not be told apart in `name`
+        qualified_arguments
+            .entry(ident)
+            .and_modify(|q| {
+                if q.as_ref() != Some(&qualified) {
+                    *q = None;
+                }
+            })
+            .or_insert(Some(qualified));
+    }
+    qualified_arguments
+}
+
+/// Applies the naming strategy and renames to the components of the finished spec,
+/// and updates the references to them.
+///
+/// # Panics
+///
+/// Will panic if two components end up with the same name
+pub(crate) fn rename_components(openapi: &mut OpenApi, names: &SchemaNames) {
+    let Some(components) = &mut openapi.components else {
+        return;
+    };
+    let mut renames: Map<String, String> = Map::new();
+    let mut schemas = Map::new();
+    for (name, schema) in std::mem::take(&mut components.schemas) {
+        let new_name = match (names.renames.get(&name), names.naming) {
+            (Some(new_name), _) => new_name.clone(),
+            (None, SchemaNaming::ModuleQualified) => {
+                names.qualified.get(&name).unwrap_or(&name).clone()
+            }
+            (None, naming) => naming.apply(&name),
+        };
+        if let Some((other, _)) = renames.iter().find(|(_, n)| **n == new_name) {
+            panic!(
+                "The schemas `{}` and `{}` are both named `{}`",
+                other, name, new_name
+            );
+        }
+        renames.insert(name, new_name.clone());
+        schemas.insert(new_name, schema);
+    }
+    components.schemas = schemas;
+    if renames.iter().all(|(old, new)| old == new) {
+        return;
+    }
+
+    let prefix = "#/components/schemas/";
+    let mut rename_refs = RenameRefs { prefix, renames };
+    for schema in components.schemas.values_mut() {
+        rename_refs.visit_schema_object(schema);
+    }
+    OpenApiGenerator::visit_components(&mut rename_refs, components);
+    for path_item in openapi.paths.values_mut() {
+        for (_, op) in OpenApiGenerator::path_item_operations(path_item) {
+            OpenApiGenerator::visit_operation(&mut rename_refs, op);
+        }
+    }
+}
+
+impl SchemaNaming {
+    fn apply(&self, name: &str) -> String {
+        match self {
+            SchemaNaming::Generated | SchemaNaming::ModuleQualified => name.to_owned(),
+            SchemaNaming::FlattenGenerics => flatten_generics(name),
+            SchemaNaming::Custom(f) => f(name),
+        }
+    }
+}
+
+/// `Job_for_TestReport` to `TestReportJob`, `Map_for_String_and_TestEvent` to `StringTestEventMap`.
+fn flatten_generics(name: &str) -> String {
+    match name.split_once("_for_") {
+        Some((base, arguments)) => {
+            let mut flat: String = arguments
+                .split("_and_")
+                .map(|argument| flatten_generics(argument).to_camel_case())
+                .collect();
+            flat.push_str(base);
+            flat
+        }
+        None => name.to_owned(),
+    }
+}
+
+/// Points the `$ref`s to the keys of `renames` at their values.
+pub(crate) struct RenameRefs<'a> {
+    pub(crate) prefix: &'a str,
+    pub(crate) renames: Map<String, String>,
+}
+impl Visitor for RenameRefs<'_> {
+    fn visit_schema_object(&mut self, schema: &mut SchemaObject) {
+        if let Some(reference) = &mut schema.reference {
+            if let Some(new_name) = reference
+                .strip_prefix(self.prefix)
+                .and_then(|name| self.renames.get(name))
+            {
+                *reference = format!("{}{new_name}", self.prefix);
+            }
+        }
+        visit_schema_object(self, schema);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{flatten_generics, module_qualified, SchemaNaming};
+    use crate::{ApiId, ApiPath, Oas3Builder};
+    use schemars::JsonSchema;
+    use serde::Serialize;
+    use serde_json::json;
+
+    mod events {
+        #[derive(serde::Serialize, schemars::JsonSchema)]
+        pub struct Status {
+            pub open: bool,
+        }
+    }
+    mod tickets {
+        #[derive(serde::Serialize, schemars::JsonSchema)]
+        pub struct Status {
+            pub sold: u64,
+        }
+    }
+
+    #[derive(Serialize, JsonSchema)]
+    pub struct CollectionWrapper<T> {
+        pub collection: Vec<T>,
+    }
+
+    #[derive(Serialize, JsonSchema)]
+    pub struct TestEvent {
+        pub status: events::Status,
+    }
+
+    #[test]
+    fn test_flatten_generics() {
+        assert_eq!("TestEvent", flatten_generics("TestEvent"));
+        assert_eq!(
+            "TestEventCollectionWrapper",
+            flatten_generics("CollectionWrapper_for_TestEvent")
+        );
+        assert_eq!(
+            "TestReportJobBatchResponse",
+            flatten_generics("BatchResponse_for_Job_for_TestReport")
+        );
+        assert_eq!(
+            "TestEventStringBatchResponse",
+            flatten_generics("BatchResponse_for_TestEvent_and_String")
+        );
+    }
+
+    #[test]
+    fn test_module_qualified_name() {
+        assert_eq!(
+            "EventsStatus",
+            module_qualified("my_crate::events::Status", "Status")
+        );
+        assert_eq!(
+            "ApiWrapper_for_EventsStatus",
+            module_qualified(
+                "my_crate::api::Wrapper<my_crate::events::Status>",
+                "Wrapper_for_Status"
+            )
+        );
+        assert_eq!(
+            "ApiWrapper_for_String",
+            module_qualified(
+                "my_crate::api::Wrapper<alloc::string::String>",
+                "Wrapper_for_String"
+            )
+        );
+        assert_eq!(
+            "ApiPair_for_Status_and_Status",
+            module_qualified(
+                "my_crate::api::Pair<my_crate::events::Status, my_crate::tickets::Status>",
+                "Pair_for_Status_and_Status"
+            )
+        );
+        assert_eq!("Status", module_qualified("Status", "Status"));
+    }
+
+    #[test]
+    fn test_naming_strategy() {
+        let events_path = ApiPath::new(Some("api".to_owned()), vec![], Some("events".to_owned()));
+        let mut oasb = Oas3Builder::default();
+        oasb.schema_naming(SchemaNaming::FlattenGenerics)
+            .rename_schema("Status", "EventStatus");
+        oasb.list::<CollectionWrapper<TestEvent>, String>(&events_path, "Events".to_owned(), None);
+        let got = serde_json::to_value(oasb.build("1.0.0".to_owned())).unwrap();
+        let _openapi_json: openapiv3::OpenAPI = serde_json::from_value(got.clone()).unwrap();
+
+        let names: Vec<&String> = got["components"]["schemas"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .collect();
+        assert_eq!(
+            vec!["EventStatus", "TestEvent", "TestEventCollectionWrapper"],
+            names
+        );
+        assert_eq!(
+            json!({"$ref": "#/components/schemas/TestEventCollectionWrapper"}),
+            got["paths"]["/api/events"]["get"]["responses"]["201"]["content"]
+                ["application/json; charset=utf-8"]["schema"]
+        );
+        assert_eq!(
+            json!({"$ref": "#/components/schemas/EventStatus"}),
+            got["components"]["schemas"]["TestEvent"]["properties"]["status"]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Two different schemas are named `Status`")]
+    fn test_name_collision() {
+        let event_path = ApiPath::new(
+            Some("api".to_owned()),
+            vec![ApiId::new("events", "{eid}")],
+            Some("status".to_owned()),
+        );
+        let ticket_path = ApiPath::new(
+            Some("api".to_owned()),
+            vec![ApiId::new("tickets", "{tid}")],
+            Some("status".to_owned()),
+        );
+        let mut oasb = Oas3Builder::default();
+        oasb.fetch::<events::Status, String>(&event_path, "Events".to_owned(), None);
+        oasb.fetch::<tickets::Status, String>(&ticket_path, "Tickets".to_owned(), None);
+    }
+
+    #[test]
+    fn test_module_qualified() {
+        let event_path = ApiPath::new(
+            Some("api".to_owned()),
+            vec![ApiId::new("events", "{eid}")],
+            Some("status".to_owned()),
+        );
+        let ticket_path = ApiPath::new(
+            Some("api".to_owned()),
+            vec![ApiId::new("tickets", "{tid}")],
+            Some("status".to_owned()),
+        );
+        let mut oasb = Oas3Builder::default();
+        oasb.schema_naming(SchemaNaming::ModuleQualified);
+        oasb.fetch::<events::Status, String>(&event_path, "Events".to_owned(), None);
+        oasb.fetch::<tickets::Status, String>(&ticket_path, "Tickets".to_owned(), None);
+        oasb.fetch::<tickets::Status, String>(
+            &ApiPath::new(Some("api".to_owned()), vec![], Some("status".to_owned())),
+            "Status".to_owned(),
+            None,
+        );
+        let got = serde_json::to_value(oasb.build("1.0.0".to_owned())).unwrap();
+        let _openapi_json: openapiv3::OpenAPI = serde_json::from_value(got.clone()).unwrap();
+
+        let schemas = &got["components"]["schemas"];
+        assert_eq!(json!(["open"]), schemas["EventsStatus"]["required"]);
+        assert_eq!(json!(["sold"]), schemas["TicketsStatus"]["required"]);
+        assert!(schemas["Status"].is_null());
+        let json = "application/json; charset=utf-8";
+        assert_eq!(
+            json!({"$ref": "#/components/schemas/EventsStatus"}),
+            got["paths"]["/api/events/{eid}/status"]["get"]["responses"]["201"]["content"][json]
+                ["schema"]
+        );
+        assert_eq!(
+            json!({"$ref": "#/components/schemas/TicketsStatus"}),
+            got["paths"]["/api/status"]["get"]["responses"]["201"]["content"][json]["schema"]
+        );
+    }
+
+    #[test]
+    fn test_module_qualified_generics() {
+        let events_path = ApiPath::new(Some("api".to_owned()), vec![], Some("events".to_owned()));
+        let tickets_path = ApiPath::new(Some("api".to_owned()), vec![], Some("tickets".to_owned()));
+        let mut oasb = Oas3Builder::default();
+        oasb.schema_naming(SchemaNaming::ModuleQualified);
+        oasb.list::<CollectionWrapper<events::Status>, String>(
+            &events_path,
+            "Events".to_owned(),
+            None,
+        );
+        oasb.list::<CollectionWrapper<tickets::Status>, String>(
+            &tickets_path,
+            "Tickets".to_owned(),
+            None,
+        );
+        let got = serde_json::to_value(oasb.build("1.0.0".to_owned())).unwrap();
+
+        let schemas = &got["components"]["schemas"];
+        assert!(schemas["TestsCollectionWrapper_for_EventsStatus"].is_object());
+        assert_eq!(
+            json!({"$ref": "#/components/schemas/TicketsStatus"}),
+            schemas["TestsCollectionWrapper_for_TicketsStatus"]["properties"]["collection"]
+                ["items"]
+        );
+        assert_eq!(json!(["sold"]), schemas["TicketsStatus"]["required"]);
+        assert_eq!(json!(["open"]), schemas["EventsStatus"]["required"]);
+    }
+
+    #[test]
+    fn test_rename_component_refs() {
+        let mut oasb = Oas3Builder::default();
+        oasb.schema_naming(SchemaNaming::FlattenGenerics);
+        oasb.response_component::<CollectionWrapper<TestEvent>>("Events", "Events".to_owned());
+        let got = serde_json::to_value(oasb.build("1.0.0".to_owned())).unwrap();
+        assert_eq!(
+            json!({"$ref": "#/components/schemas/TestEventCollectionWrapper"}),
+            got["components"]["responses"]["Events"]["content"]["application/json; charset=utf-8"]
+                ["schema"]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "are both named `TestEvent`")]
+    fn test_rename_collision() {
+        let events_path = ApiPath::new(Some("api".to_owned()), vec![], Some("events".to_owned()));
+        let mut oasb = Oas3Builder::default();
+        oasb.rename_schema("Status", "TestEvent");
+        oasb.list::<Vec<TestEvent>, String>(&events_path, "Events".to_owned(), None);
+        let _spec = oasb.build("1.0.0".to_owned());
+    }
+}
@@ -2,20 +2,35 @@ mod securityscheme;
 pub use securityscheme::*;
 
 use crate::apipath::ApiPath;
+use crate::components::dedup_components;
+use crate::discriminator::add_discriminators;
+use crate::examples::{validate_examples, ExampleError};
 use crate::generator::{
-    Components, Info, MediaType, OpenApi, OpenApiGenerator, Parameter, ParameterValue, RefOr,
-    RequestBody, Response, Responses, SecurityScheme,
+    Components, Info, MediaType, OpenApi, OpenApiGenerator, Operation, Parameter, ParameterValue,
+    RefOr, RequestBody, Response, Responses, SecurityScheme,
 };
+use crate::naming::{rename_components, SchemaNames};
+use crate::readwrite::{split_components, ReadWriteMode};
+use crate::smoketests::add_smoke_tests;
+use crate::synthesis::synthesize_examples;
+use crate::transform::SpecTransform;
 use contracts::requires;
 use heck::CamelCase;
 use schemars::gen::{SchemaGenerator, SchemaSettings};
 use schemars::{JsonSchema, Map};
 use serde::Serialize;
 use serde_json::Value;
+use std::sync::Arc;
 
 #[derive(Debug, Clone)]
 pub struct Oas3Builder {
     pub(crate) generator: OpenApiGenerator,
+    pub(crate) read_write_mode: ReadWriteMode,
+    pub(crate) schema_names: SchemaNames,
+    pub(crate) transforms: Vec<Arc<dyn SpecTransform>>,
+    pub(crate) dedup_components: bool,
+    pub(crate) example_seed: Option<u64>,
+    pub(crate) smoke_tests: bool,
 }
 impl Default for Oas3Builder {
     fn default() -> Self {
@@ -24,29 +39,75 @@ impl Default for Oas3Builder {
 }
 impl From<OpenApiGenerator> for Oas3Builder {
     fn from(generator: OpenApiGenerator) -> Self {
-        Oas3Builder { generator }
+        Oas3Builder {
+            generator,
+            read_write_mode: ReadWriteMode::default(),
+            schema_names: SchemaNames::default(),
+            transforms: vec![],
+            dedup_components: false,
+            example_seed: None,
+            smoke_tests: false,
+        }
     }
 }
 impl Oas3Builder {
     #[must_use]
     pub fn new() -> Self {
+        Oas3Builder::with_settings(Oas3Builder::default_settings())
+    }
+
+    /// Generates every schema of the spec with `settings`, e.g. to emit `Option<T>`
+    /// as `nullable: true`, to inline subschemas or to add visitors.
+    /// The visitors are applied to the components and operation schemas by `build`.
+    /// ```
+    /// # use oas_gen::Oas3Builder;
+    /// let mut settings = Oas3Builder::default_settings();
+    /// settings.option_nullable = true;
+    /// let oasb = Oas3Builder::with_settings(settings);
+    /// ```
+    #[must_use]
+    pub fn with_settings(settings: SchemaSettings) -> Self {
+        Oas3Builder {
+            generator: OpenApiGenerator::new(SchemaGenerator::new(settings)),
+            read_write_mode: ReadWriteMode::default(),
+            schema_names: SchemaNames::default(),
+            transforms: vec![],
+            dedup_components: false,
+            example_seed: None,
+            smoke_tests: false,
+        }
+    }
+
+    /// The settings used by `new`: `OpenAPI` 3.0 schemas where `Option<T>` is an
+    /// optional, non nullable property.
+    #[must_use]
+    pub fn default_settings() -> SchemaSettings {
         let mut sts = SchemaSettings::openapi3();
         sts.option_add_null_type = false;
         sts.option_nullable = false;
-        Oas3Builder {
-            generator: OpenApiGenerator::new(SchemaGenerator::new(sts)),
-        }
+        sts
     }
 
+    /// # Panics
+    ///
+    /// Will panic if an example does not match its schema, see `try_build`
     #[must_use]
     pub fn build(self, version: String) -> OpenApi {
-        let mut openapi = self.generator.into_openapi();
-        openapi.info = Info {
-            version,
-            ..openapi.info
-        };
-        // openapi.security = Vec<SecurityRequirement>
-        openapi
+        match self.try_build(version) {
+            Ok(openapi) => openapi,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Builds the spec, validating the examples of request bodies, responses and
+    /// parameters against their schema.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` with the location and JSON pointer of the first example
+    /// not matching its schema.
+    pub fn try_build(self, version: String) -> Result<OpenApi, ExampleError> {
+        self.finish(version, None)
     }
 
     ///
@@ -55,23 +116,93 @@ impl Oas3Builder {
     /// let security_scheme = create_bearer_scheme();
     /// security_schemes.insert("bearerAuth".to_owned(), RefOr::Object(security_scheme));
     /// ```
+    ///
+    /// # Panics
+    ///
+    /// Will panic if an example does not match its schema, see `try_build`
     #[must_use]
     pub fn build_with_security(
         self,
         version: String,
         security_schemes: Map<String, RefOr<SecurityScheme>>,
     ) -> OpenApi {
-        let mut security: Map<String, Vec<String>> = Map::default();
-        security.insert("bearerAuth".to_owned(), vec![]);
+        match self.try_build_with_security(version, security_schemes) {
+            Ok(openapi) => openapi,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// `build_with_security`, validating the examples like `try_build`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` with the location and JSON pointer of the first example
+    /// not matching its schema.
+    pub fn try_build_with_security(
+        self,
+        version: String,
+        security_schemes: Map<String, RefOr<SecurityScheme>>,
+    ) -> Result<OpenApi, ExampleError> {
+        self.finish(version, Some(security_schemes))
+    }
 
-        let mut openapi = self.build(version);
-        let components = Components {
-            security_schemes,
-            ..openapi.components.unwrap_or_default()
+    /// The spec with the `bearerAuth` requirement of `security_schemes` if any;
+    /// the transforms run last but for `dedup_components`, so they see the whole spec
+    /// before its duplicates are hoisted into the components.
+    fn finish(
+        self,
+        version: String,
+        security_schemes: Option<Map<String, RefOr<SecurityScheme>>>,
+    ) -> Result<OpenApi, ExampleError> {
+        let mut openapi = self.generator.into_openapi();
+        openapi.info = Info {
+            version,
+            ..openapi.info
         };
-        openapi.components = Some(components);
-        openapi.security.push(security);
-        openapi
+        if let Some(security_schemes) = security_schemes {
+            let mut security: Map<String, Vec<String>> = Map::default();
+            security.insert("bearerAuth".to_owned(), vec![]);
+            let components = Components {
+                security_schemes,
+                ..openapi.components.unwrap_or_default()
+            };
+            openapi.components = Some(components);
+            openapi.security.push(security);
+        }
+        rename_components(&mut openapi, &self.schema_names);
+        add_discriminators(&mut openapi);
+        if self.read_write_mode == ReadWriteMode::SplitComponents {
+            split_components(&mut openapi);
+        }
+        validate_examples(&mut openapi)?;
+        if let Some(seed) = self.example_seed {
+            synthesize_examples(&mut openapi, seed);
+        }
+        if self.smoke_tests {
+            add_smoke_tests(&mut openapi);
+        }
+        for transform in &self.transforms {
+            transform.transform(&mut openapi);
+        }
+        if self.dedup_components {
+            dedup_components(&mut openapi);
+        }
+        Ok(openapi)
+    }
+
+    /// # Panics
+    ///
+    /// Will panic if no operation has been added for `method` `web_path`
+    pub(crate) fn registered_operation(
+        &mut self,
+        web_path: &ApiPath,
+        method: &http::Method,
+    ) -> &mut Operation {
+        let path = web_path.to_string();
+        match self.generator.operation_mut(&path, method) {
+            Some(op) => op,
+            None => panic!("No operation has been added for {} {}", method, path),
+        }
     }
 
     pub(crate) fn create_response<O: JsonSchema + Serialize>(
@@ -79,8 +210,7 @@ impl Oas3Builder {
         description: String,
     ) -> Response {
         let content_type = "application/json; charset=utf-8".to_owned();
-        let schema: schemars::schema::SchemaObject =
-            self.generator.schema_generator.subschema_for::<O>().into();
+        let schema: schemars::schema::SchemaObject = self.register_schema::<O>().into();
         // OAS3 requires that if InstanceType::Null then ommit content entirely
         let ommit_content =
             if let Some(schemars::schema::SingleOrVec::Single(some)) = &schema.instance_type {
@@ -105,8 +235,7 @@ impl Oas3Builder {
     pub(crate) fn create_request_body<I: JsonSchema + Serialize>(
         &mut self,
     ) -> Option<RefOr<RequestBody>> {
-        let schema: schemars::schema::SchemaObject =
-            self.generator.schema_generator.subschema_for::<I>().into();
+        let schema: schemars::schema::SchemaObject = self.register_schema::<I>().into();
         // OAS3 requires that if InstanceType::Null then ommit content entirely
         let ommit_content =
             if let Some(schemars::schema::SingleOrVec::Single(some)) = &schema.instance_type {
@@ -219,7 +348,8 @@ impl Oas3Builder {
             }
         }
         for query_param in api_path.query_params {
-            parameters.push(query_param.into());
+            let param = query_param.build_with(&mut self.generator.schema_generator);
+            parameters.push(param.into());
         }
     }
 }
@@ -227,6 +357,11 @@ impl Oas3Builder {
 #[cfg(test)]
 mod tests {
     use super::Oas3Builder;
+    use crate::{ApiId, ApiPath, QueryParamBuilder};
+    use schemars::schema::SchemaObject;
+    use schemars::visit::{visit_schema_object, Visitor};
+    use schemars::JsonSchema;
+    use serde::Serialize;
     use serde_json::json;
 
     #[test]
@@ -248,4 +383,84 @@ mod tests {
 
         assert_eq!(resp, None);
     }
+
+    #[test]
+    fn test_query_param_definitions_are_shared() {
+        #[derive(Serialize, JsonSchema)]
+        #[allow(dead_code)]
+        pub enum EventStatus {
+            Draft,
+            Published,
+        }
+
+        let status_param =
+            QueryParamBuilder::new::<EventStatus>("status".to_owned(), Some(EventStatus::Draft));
+        let list_path = ApiPath::with_queries(
+            Some("api".to_owned()),
+            vec![],
+            Some("events".to_owned()),
+            vec![status_param],
+        );
+        let mut oasb = Oas3Builder::default();
+        oasb.list::<Vec<String>, String>(&list_path, "Events".to_owned(), None);
+
+        let got = serde_json::to_value(oasb.build("1.0.0".to_owned())).unwrap();
+        assert_eq!(
+            json!({"$ref": "#/components/schemas/EventStatus"}),
+            got["paths"]["/api/events"]["get"]["parameters"][0]["schema"]
+        );
+        assert_eq!(
+            json!({"type": "string", "enum": ["Draft", "Published"]}),
+            got["components"]["schemas"]["EventStatus"]
+        );
+    }
+
+    #[test]
+    fn test_with_settings() {
+        #[derive(Serialize, JsonSchema)]
+        pub struct TestVenue {
+            pub name: String,
+        }
+        #[derive(Serialize, JsonSchema)]
+        pub struct TestEvent {
+            pub note: Option<String>,
+            pub venue: Option<TestVenue>,
+        }
+
+        /// Titles every object schema.
+        #[derive(Debug, Clone)]
+        struct TitleObjects;
+        impl Visitor for TitleObjects {
+            fn visit_schema_object(&mut self, schema: &mut SchemaObject) {
+                if schema.object.is_some() {
+                    schema.metadata().title = Some("Object".to_owned());
+                }
+                visit_schema_object(self, schema);
+            }
+        }
+
+        let mut settings = Oas3Builder::default_settings();
+        settings.option_nullable = true;
+        settings.visitors.push(Box::new(TitleObjects));
+        let mut oasb = Oas3Builder::with_settings(settings);
+        let fetch_path = ApiPath::new(
+            Some("api".to_owned()),
+            vec![ApiId::new("events", "{eid}")],
+            None,
+        );
+        oasb.fetch::<TestEvent, String>(&fetch_path, "Events".to_owned(), None);
+        let got = serde_json::to_value(oasb.build("1.0.0".to_owned())).unwrap();
+        let _openapi_json: openapiv3::OpenAPI = serde_json::from_value(got.clone()).unwrap();
+
+        let event = &got["components"]["schemas"]["TestEvent"];
+        assert_eq!(json!("Object"), event["title"]);
+        assert_eq!(
+            json!({"type": "string", "nullable": true}),
+            event["properties"]["note"]
+        );
+        assert_eq!(
+            json!({"allOf": [{"$ref": "#/components/schemas/TestVenue"}], "nullable": true}),
+            event["properties"]["venue"]
+        );
+    }
 }
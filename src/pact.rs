@@ -0,0 +1,556 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Map as JsonMap, Value};
+
+use crate::examples::media_example;
+use crate::generator::{OpenApi, Operation, ParameterValue, RefOr};
+use crate::runner::{encode, encode_param};
+use crate::xtests::{
+    captured_names, operation_of, order_tests, tested_operations, BodyAssertion, Test,
+    TestOrderError, TestedOperation,
+};
+
+/// Exports the `x-tests` and the examples of a spec as a Pact v3 contract between
+/// `consumer` and `provider`, to verify the provider with the consumers' tooling.
+///
+/// - Every test becomes an interaction; its dependencies become provider states,
+///   and captured variables in the path, e.g. `{eid}`, provider state generators.
+///   A test allowing several statuses expects the first one.
+/// - Every operation with a success response example becomes an interaction
+///   sending the parameter and request body examples, matching the response by type.
+/// ```
+/// # use oas_gen::{Oas3Builder, PactExporter};
+/// # let openapi = Oas3Builder::default().build("1.0.0".to_owned());
+/// let pact = PactExporter::new("events-ui", "events-api").export(&openapi).unwrap();
+/// assert_eq!("3.0.0", pact["metadata"]["pactSpecification"]["version"]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct PactExporter {
+    consumer: String,
+    provider: String,
+    tests: bool,
+    examples: bool,
+}
+
+impl PactExporter {
+    #[must_use]
+    pub fn new(consumer: &str, provider: &str) -> Self {
+        PactExporter {
+            consumer: consumer.to_owned(),
+            provider: provider.to_owned(),
+            tests: true,
+            examples: true,
+        }
+    }
+
+    /// Whether to export the `x-tests`, defaults to true.
+    #[must_use]
+    pub fn tests(mut self, tests: bool) -> Self {
+        self.tests = tests;
+        self
+    }
+
+    /// Whether to export the examples, defaults to true.
+    #[must_use]
+    pub fn examples(mut self, examples: bool) -> Self {
+        self.examples = examples;
+        self
+    }
+
+    /// The Pact file.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the dependencies between the tests can not be satisfied.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if an `x-tests` extension is not a list of tests
+    pub fn export(&self, openapi: &OpenApi) -> Result<Value, TestOrderError> {
+        let operations = tested_operations(openapi);
+
+        let mut interactions = vec![];
+        if self.tests {
+            let ordered = order_tests(operations.iter().flat_map(|op| &op.tests))?;
+            let captured = captured_names(&operations);
+            for test in ordered.iter().filter(|test| !test.skip) {
+                let TestedOperation { path, method, .. } = operation_of(&operations, test);
+                let provider_states: Vec<&str> = test
+                    .depends_on
+                    .iter()
+                    .filter_map(|id| ordered.iter().find(|t| &t.id == id))
+                    .map(|t| t.description.as_str())
+                    .collect();
+                interactions.push(test_interaction(
+                    path,
+                    method.as_str(),
+                    test,
+                    &provider_states,
+                    &captured,
+                ));
+            }
+        }
+        if self.examples {
+            for TestedOperation {
+                path,
+                method,
+                operation,
+                ..
+            } in &operations
+            {
+                interactions.extend(example_interaction(path, method.as_str(), operation));
+            }
+        }
+
+        Ok(json!({
+            "consumer": {"name": self.consumer},
+            "provider": {"name": self.provider},
+            "interactions": interactions,
+            "metadata": {"pactSpecification": {"version": "3.0.0"}}
+        }))
+    }
+
+    /// Writes the Pact file of `export` as `{consumer}-{provider}.json` into `dir`,
+    /// returns its path.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the tests can not be ordered or the file can not be written.
+    pub fn write_to(&self, openapi: &OpenApi, dir: &Path) -> io::Result<PathBuf> {
+        let pact = self
+            .export(openapi)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(format!("{}-{}.json", self.consumer, self.provider));
+        let json = serde_json::to_string_pretty(&pact).map_err(io::Error::other)?;
+        std::fs::write(&path, json)?;
+        Ok(path)
+    }
+}
+
+fn test_interaction(
+    path: &str,
+    method: &str,
+    test: &Test,
+    provider_states: &[&str],
+    captured: &BTreeSet<String>,
+) -> Value {
+    let mut path = path.to_owned();
+    for (name, value) in &test.params {
+        let value = encode_param(value, |name| captured.contains(name));
+        path = path.replace(&format!("{{{name}}}"), &value);
+    }
+
+    let mut request = JsonMap::new();
+    let mut request_rules = JsonMap::new();
+    // captured variables are supplied by the provider states
+    let mut generators = JsonMap::new();
+    request.insert("method".to_owned(), json!(method));
+    if let Some(generator) = provider_state(&path) {
+        generators.insert("path".to_owned(), generator);
+    }
+    request.insert("path".to_owned(), json!(path));
+    let query: BTreeMap<_, _> = test
+        .request
+        .query
+        .iter()
+        .map(|(name, value)| (name.clone(), vec![value.clone()]))
+        .collect();
+    if !query.is_empty() {
+        request.insert("query".to_owned(), json!(query));
+        let query_generators = value_generators(&test.request.query);
+        if !query_generators.is_empty() {
+            generators.insert("query".to_owned(), Value::Object(query_generators));
+        }
+    }
+    let mut headers = test.request.headers.clone();
+    if test.auth {
+        headers.insert("Authorization".to_owned(), "Bearer token".to_owned());
+        request_rules.insert(
+            "header".to_owned(),
+            json!({"Authorization": {"matchers": [{"match": "regex", "regex": "^Bearer .+$"}]}}),
+        );
+    }
+    if !test.request.cookie.is_empty() {
+        headers.insert("Cookie".to_owned(), test.request.cookie.clone());
+    }
+    if !test.request.body.is_null() {
+        headers.insert("Content-Type".to_owned(), "application/json".to_owned());
+        request.insert("body".to_owned(), test.request.body.clone());
+        let mut body_generators = JsonMap::new();
+        add_body_generators(&test.request.body, "$", &mut body_generators);
+        if !body_generators.is_empty() {
+            generators.insert("body".to_owned(), Value::Object(body_generators));
+        }
+    }
+    if !headers.is_empty() {
+        request.insert("headers".to_owned(), json!(headers));
+        let header_generators = value_generators(&headers);
+        if !header_generators.is_empty() {
+            generators.insert("header".to_owned(), Value::Object(header_generators));
+        }
+    }
+    if !request_rules.is_empty() {
+        request.insert("matchingRules".to_owned(), Value::Object(request_rules));
+    }
+    if !generators.is_empty() {
+        request.insert("generators".to_owned(), Value::Object(generators));
+    }
+
+    let response = test_response(test);
+
+    let mut interaction = JsonMap::new();
+    interaction.insert("description".to_owned(), json!(test.description));
+    if !provider_states.is_empty() {
+        let provider_states: Vec<_> = provider_states
+            .iter()
+            .map(|name| json!({"name": name}))
+            .collect();
+        interaction.insert("providerStates".to_owned(), json!(provider_states));
+    }
+    interaction.insert("request".to_owned(), Value::Object(request));
+    interaction.insert("response".to_owned(), Value::Object(response));
+    Value::Object(interaction)
+}
+
+fn test_response(test: &Test) -> JsonMap<String, Value> {
+    let mut response = JsonMap::new();
+    // a Pact response has a single status
+    let status = test.response.status.first().copied().unwrap_or(200);
+    response.insert("status".to_owned(), json!(status));
+    if !test.response.headers.is_empty() {
+        response.insert("headers".to_owned(), json!(test.response.headers));
+    }
+    // Pact ignores unexpected properties of the provider's response bodies,
+    // so subsets are compared as bodies too, their arrays by the type of the items
+    let body = test
+        .response
+        .body
+        .iter()
+        .find_map(|assertion| match assertion {
+            BodyAssertion::Exact(body) => Some((body, false)),
+            BodyAssertion::Subset(body) => Some((body, true)),
+            _ => None,
+        });
+    if let Some((body, subset)) = body {
+        response.insert("body".to_owned(), body.clone());
+        let mut body_rules = JsonMap::new();
+        if subset {
+            add_array_rules(body, "$", &mut body_rules);
+        }
+        if !body_rules.is_empty() {
+            response.insert("matchingRules".to_owned(), json!({ "body": body_rules }));
+        }
+        let mut body_generators = JsonMap::new();
+        add_body_generators(body, "$", &mut body_generators);
+        if !body_generators.is_empty() {
+            response.insert("generators".to_owned(), json!({ "body": body_generators }));
+        }
+    }
+    response
+}
+
+/// The generator filling the `{name}` placeholders of `value` from the provider state.
+fn provider_state(value: &str) -> Option<Value> {
+    value.contains('{').then(|| {
+        let expression = value.replace('{', "${");
+        json!({"type": "ProviderState", "expression": expression})
+    })
+}
+
+fn value_generators(values: &BTreeMap<String, String>) -> JsonMap<String, Value> {
+    values
+        .iter()
+        .filter_map(|(name, value)| Some((name.clone(), provider_state(value)?)))
+        .collect()
+}
+
+fn add_body_generators(body: &Value, path: &str, generators: &mut JsonMap<String, Value>) {
+    match body {
+        Value::String(value) => {
+            if let Some(generator) = provider_state(value) {
+                generators.insert(path.to_owned(), generator);
+            }
+        }
+        Value::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                add_body_generators(item, &format!("{path}[{index}]"), generators);
+            }
+        }
+        Value::Object(object) => {
+            for (key, value) in object {
+                add_body_generators(value, &child_path(path, key), generators);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Matches every array of `body` by the type of its items, with at least as many
+/// items as expected.
+fn add_array_rules(body: &Value, path: &str, rules: &mut JsonMap<String, Value>) {
+    match body {
+        Value::Array(items) => {
+            rules.insert(
+                path.to_owned(),
+                json!({"matchers": [{"match": "type", "min": items.len()}]}),
+            );
+            for item in items {
+                add_array_rules(item, &format!("{path}[*]"), rules);
+            }
+        }
+        Value::Object(object) => {
+            for (key, value) in object {
+                add_array_rules(value, &child_path(path, key), rules);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn child_path(path: &str, key: &str) -> String {
+    if key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        format!("{path}.{key}")
+    } else {
+        format!("{path}['{key}']")
+    }
+}
+
+/// The interaction of the lowest 2xx response having an example, if any and all
+/// required parameters and request body have examples.
+fn example_interaction(path: &str, method: &str, op: &Operation) -> Option<Value> {
+    let (status, response_example) = op
+        .responses
+        .responses
+        .iter()
+        .filter(|(status, _)| status.starts_with('2'))
+        .find_map(|(status, response)| match response {
+            RefOr::Object(response) => {
+                let example = response.content.values().find_map(media_example)?;
+                Some((status.parse::<u16>().ok()?, example))
+            }
+            RefOr::Ref(_) => None,
+        })?;
+
+    let mut path = path.to_owned();
+    let mut query = BTreeMap::new();
+    for parameter in &op.parameters {
+        let RefOr::Object(parameter) = parameter else {
+            continue;
+        };
+        let example = match &parameter.value {
+            ParameterValue::Schema { example, .. } => example.as_ref(),
+            ParameterValue::Content { .. } => None,
+        };
+        let value = match example {
+            Some(Value::String(text)) => text.clone(),
+            Some(example) => example.to_string(),
+            None if parameter.required => return None,
+            None => continue,
+        };
+        match parameter.location.as_str() {
+            "path" => path = path.replace(&format!("{{{}}}", parameter.name), &encode(&value)),
+            "query" => {
+                query.insert(parameter.name.clone(), vec![value]);
+            }
+            _ => {}
+        }
+    }
+
+    let mut request = JsonMap::new();
+    request.insert("method".to_owned(), json!(method));
+    request.insert("path".to_owned(), json!(path));
+    if !query.is_empty() {
+        request.insert("query".to_owned(), json!(query));
+    }
+    if let Some(RefOr::Object(request_body)) = &op.request_body {
+        match request_body.content.values().find_map(media_example) {
+            Some(body) => {
+                request.insert(
+                    "headers".to_owned(),
+                    json!({"Content-Type": "application/json"}),
+                );
+                request.insert("body".to_owned(), body);
+            }
+            None if request_body.required => return None,
+            None => {}
+        }
+    }
+
+    let name = op.operation_id.clone().unwrap_or_default();
+    Some(json!({
+        "description": format!("{name} example"),
+        "request": request,
+        "response": {
+            "status": status,
+            "body": response_example,
+            "matchingRules": {"body": {"$": {"matchers": [{"match": "type"}]}}}
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PactExporter;
+    use crate::generator::{Parameter, ParameterValue, RefOr};
+    use crate::xtests::{BodyAssertion, Test};
+    use crate::{ApiId, ApiPath, Oas3Builder};
+    use schemars::JsonSchema;
+    use serde::Serialize;
+    use serde_json::json;
+
+    #[derive(Serialize, JsonSchema)]
+    pub struct TestEvent {
+        pub id: String,
+        pub title: String,
+    }
+
+    #[test]
+    fn test_export() {
+        let events_path = ApiPath::new(Some("api".to_owned()), vec![], Some("events".to_owned()));
+        let event_path = ApiPath::new(
+            Some("api".to_owned()),
+            vec![ApiId::new("events", "{eid}")],
+            None,
+        );
+        let create = Test::new("Create an event.")
+            .id("create")
+            .auth()
+            .json_body(&json!({"title": "Hackaton"}))
+            .expect_status(201)
+            .expect_body(BodyAssertion::Subset(json!({"title": "Hackaton"})))
+            .capture("eid", "$.id")
+            .build();
+        let fetch = Test::new("Fetch the created event.")
+            .depends_on("create")
+            .param("eid", "{eid}")
+            .query("since", "{eid}")
+            .header("If-None-Match", "{eid}")
+            .expect_status(201)
+            .expect_body(BodyAssertion::Subset(json!({"id": "{eid}", "tags": ["a"]})))
+            .build();
+        let mut oasb = Oas3Builder::default();
+        oasb.fetch_with_tests::<TestEvent, String>(
+            &event_path,
+            "Events".to_owned(),
+            None,
+            &[fetch],
+        );
+        oasb.create_with_tests::<TestEvent, TestEvent, String>(
+            &events_path,
+            "Events".to_owned(),
+            None,
+            &[create],
+        );
+        let event = json!({"id": "e1", "title": "Hackaton"});
+        oasb.response_example(&event_path, &http::Method::GET, 201, &event);
+        let openapi = oasb.build("1.0.0".to_owned());
+
+        let pact = PactExporter::new("events-ui", "events-api")
+            .export(&openapi)
+            .unwrap();
+        assert_eq!(json!({"name": "events-api"}), pact["provider"]);
+        let interactions = pact["interactions"].as_array().unwrap();
+        assert_eq!(3, interactions.len());
+        assert_eq!(
+            json!({
+                "description": "Create an event.",
+                "request": {
+                    "method": "POST",
+                    "path": "/api/events",
+                    "headers": {"Authorization": "Bearer token", "Content-Type": "application/json"},
+                    "body": {"title": "Hackaton"},
+                    "matchingRules": {"header": {"Authorization": {"matchers": [{"match": "regex", "regex": "^Bearer .+$"}]}}}
+                },
+                "response": {"status": 201, "body": {"title": "Hackaton"}}
+            }),
+            interactions[0]
+        );
+        assert_eq!(
+            json!({
+                "description": "Fetch the created event.",
+                "providerStates": [{"name": "Create an event."}],
+                "request": {
+                    "method": "GET",
+                    "path": "/api/events/{eid}",
+                    "query": {"since": ["{eid}"]},
+                    "headers": {"If-None-Match": "{eid}"},
+                    "generators": {
+                        "path": {"type": "ProviderState", "expression": "/api/events/${eid}"},
+                        "query": {"since": {"type": "ProviderState", "expression": "${eid}"}},
+                        "header": {"If-None-Match": {"type": "ProviderState", "expression": "${eid}"}}
+                    }
+                },
+                "response": {
+                    "status": 201,
+                    "body": {"id": "{eid}", "tags": ["a"]},
+                    "matchingRules": {"body": {"$.tags": {"matchers": [{"match": "type", "min": 1}]}}},
+                    "generators": {"body": {"$.id": {"type": "ProviderState", "expression": "${eid}"}}}
+                }
+            }),
+            interactions[1]
+        );
+        assert_eq!(
+            json!({
+                "description": "fetchEvents example",
+                "request": {"method": "GET", "path": "/api/events/8472"},
+                "response": {
+                    "status": 201,
+                    "body": event,
+                    "matchingRules": {"body": {"$": {"matchers": [{"match": "type"}]}}}
+                }
+            }),
+            interactions[2]
+        );
+    }
+
+    #[test]
+    fn test_encoded_path() {
+        let event_path = ApiPath::new(
+            Some("api".to_owned()),
+            vec![ApiId::new("events", "{eid}")],
+            None,
+        );
+        let fetch = Test::new("Fetch an event.")
+            .param("eid", "2024/{year} Hackaton")
+            .expect_status(201)
+            .build();
+        let mut oasb = Oas3Builder::default();
+        oasb.fetch_with_tests::<TestEvent, String>(
+            &event_path,
+            "Events".to_owned(),
+            None,
+            &[fetch],
+        );
+        let event = json!({"id": "e1", "title": "Hackaton"});
+        oasb.response_example(&event_path, &http::Method::GET, 201, &event);
+        let mut openapi = oasb.build("1.0.0".to_owned());
+        let path_item = openapi.paths.get_mut("/api/events/{eid}").unwrap();
+        let op = path_item.get.as_mut().unwrap();
+        for parameter in &mut op.parameters {
+            if let RefOr::Object(Parameter {
+                value: ParameterValue::Schema { example, .. },
+                ..
+            }) = parameter
+            {
+                *example = Some(json!("e/1"));
+            }
+        }
+
+        let pact = PactExporter::new("events-ui", "events-api")
+            .export(&openapi)
+            .unwrap();
+        let interactions = pact["interactions"].as_array().unwrap();
+        // the braces are not a captured variable, there is no generator
+        assert_eq!(
+            json!({"method": "GET", "path": "/api/events/2024%2F%7Byear%7D%20Hackaton"}),
+            interactions[0]["request"]
+        );
+        assert_eq!(
+            json!({"method": "GET", "path": "/api/events/e%2F1"}),
+            interactions[1]["request"]
+        );
+    }
+}
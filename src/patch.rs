@@ -0,0 +1,446 @@
+use std::collections::BTreeSet;
+
+use schemars::schema::{Schema, SchemaObject, SubschemaValidation};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::apipath::ApiPath;
+use crate::generator::{
+    MediaType, Operation, OperationInfo, Parameter, RefOr, RequestBody, Responses,
+};
+use crate::oasgen::Oas3Builder;
+use crate::xtests::{tests_extensions, Test};
+
+pub const MERGE_PATCH_CONTENT_TYPE: &str = "application/merge-patch+json";
+pub const JSON_PATCH_CONTENT_TYPE: &str = "application/json-patch+json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum JsonPatchOp {
+    Add,
+    Remove,
+    Replace,
+    Move,
+    Copy,
+    Test,
+}
+
+/// A single operation of a JSON Patch document, see RFC 6902.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct JsonPatchOperation {
+    pub op: JsonPatchOp,
+    /// A JSON Pointer to the target location, e.g. `/title`.
+    pub path: String,
+    /// The value to `add`, `replace` or `test` against.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<Value>,
+    /// A JSON Pointer to the source location of `move` and `copy`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+}
+
+impl Oas3Builder {
+    /// Registers a `PATCH` taking an `application/merge-patch+json` body, see RFC 7396.
+    /// The body schema is derived from `I` and registered as `{I}Patch`: every property
+    /// is optional and nullable, where `null` removes the property.
+    pub fn update_merge_patch<
+        I: JsonSchema + Serialize,
+        O: JsonSchema + Serialize,
+        E: JsonSchema + Serialize,
+    >(
+        &mut self,
+        web_path: &ApiPath,
+        document_name: String,
+        operation_description: Option<String>,
+    ) {
+        self.update_merge_patch_with_tests::<I, O, E>(
+            web_path,
+            document_name,
+            operation_description,
+            &[],
+        );
+    }
+
+    /// # Panics
+    ///
+    /// Will panic if json serialization of `tests` fail
+    pub fn update_merge_patch_with_tests<
+        I: JsonSchema + Serialize,
+        O: JsonSchema + Serialize,
+        E: JsonSchema + Serialize,
+    >(
+        &mut self,
+        web_path: &ApiPath,
+        document_name: String,
+        operation_description: Option<String>,
+        tests: &[Test],
+    ) {
+        let schema = self.merge_patch_schema_for::<I>();
+        let request_body = Self::create_patch_request_body(schema, MERGE_PATCH_CONTENT_TYPE);
+        self.add_patch_operation::<O, E>(
+            web_path,
+            document_name,
+            operation_description,
+            request_body,
+            tests,
+        );
+    }
+
+    /// Registers a `PATCH` taking an `application/json-patch+json` body, an array of
+    /// `JsonPatchOperation`s, see RFC 6902.
+    pub fn update_json_patch<O: JsonSchema + Serialize, E: JsonSchema + Serialize>(
+        &mut self,
+        web_path: &ApiPath,
+        document_name: String,
+        operation_description: Option<String>,
+    ) {
+        self.update_json_patch_with_tests::<O, E>(
+            web_path,
+            document_name,
+            operation_description,
+            &[],
+        );
+    }
+
+    /// # Panics
+    ///
+    /// Will panic if json serialization of `tests` fail
+    pub fn update_json_patch_with_tests<O: JsonSchema + Serialize, E: JsonSchema + Serialize>(
+        &mut self,
+        web_path: &ApiPath,
+        document_name: String,
+        operation_description: Option<String>,
+        tests: &[Test],
+    ) {
+        let schema = self
+            .generator
+            .schema_generator
+            .subschema_for::<Vec<JsonPatchOperation>>()
+            .into();
+        let request_body = Self::create_patch_request_body(schema, JSON_PATCH_CONTENT_TYPE);
+        self.add_patch_operation::<O, E>(
+            web_path,
+            document_name,
+            operation_description,
+            request_body,
+            tests,
+        );
+    }
+
+    /// A reference to the `{I}Patch` definition, which is `I` with every property
+    /// optional and nullable. Merge patches apply recursively, so referenced and
+    /// inline objects are relaxed too, referenced ones as `{Name}Patch` definitions;
+    /// arrays are replaced as a whole and keep their schema.
+    /// Non object schemas are returned unchanged.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if a different schema is already named `{Name}Patch`; registering
+    /// a type of that name afterwards panics too, see `register_schema`
+    pub(crate) fn merge_patch_schema_for<I: JsonSchema>(&mut self) -> SchemaObject {
+        let schema = self.register_schema::<I>();
+        let definition = match self.generator.schema_generator.dereference(&schema) {
+            Some(Schema::Object(definition)) => definition.clone(),
+            _ => schema.clone().into_object(),
+        };
+        if definition.object.is_none() {
+            return schema.into_object();
+        }
+        self.merge_patch_definition(&I::schema_name(), definition, &mut BTreeSet::new())
+    }
+
+    /// A reference to `{name}Patch`, the merge patch of the object schema `definition`.
+    /// `in_progress` holds the patches being built, which recursive types reference.
+    fn merge_patch_definition(
+        &mut self,
+        name: &str,
+        definition: SchemaObject,
+        in_progress: &mut BTreeSet<String>,
+    ) -> SchemaObject {
+        let patch_name = format!("{name}Patch");
+        let reference = format!(
+            "{}{}",
+            self.generator.schema_generator.settings().definitions_path,
+            patch_name
+        );
+        if in_progress.insert(patch_name.clone()) {
+            let mut patch = self.relax(definition, in_progress);
+            patch.metadata().description = Some(format!(
+                "JSON Merge Patch of {name}, a `null` property is removed."
+            ));
+            let patch = Schema::Object(patch);
+            let definitions = self.generator.schema_generator.definitions_mut();
+            if let Some(registered) = definitions.get(&patch_name) {
+                assert!(
+                    *registered == patch,
+                    "The merge patch of `{}` is named `{}` like a different schema, rename one with #[schemars(rename = \"..\")]",
+                    name,
+                    patch_name
+                );
+            }
+            definitions.insert(patch_name.clone(), patch);
+            self.schema_names.patches.insert(patch_name);
+        }
+        SchemaObject::new_ref(reference)
+    }
+
+    /// `schema` with every property optional and nullable, and relaxed in turn.
+    fn relax(
+        &mut self,
+        mut schema: SchemaObject,
+        in_progress: &mut BTreeSet<String>,
+    ) -> SchemaObject {
+        let Some(object) = &mut schema.object else {
+            return schema;
+        };
+        object.required.clear();
+        let properties = std::mem::take(&mut object.properties);
+        for (name, property) in properties {
+            let property = match property {
+                Schema::Object(property) => match &property.reference {
+                    Some(reference) => {
+                        let definition_name = reference.rsplit('/').next().unwrap_or_default();
+                        match self
+                            .generator
+                            .schema_generator
+                            .definitions()
+                            .get(definition_name)
+                        {
+                            Some(Schema::Object(definition)) if definition.object.is_some() => {
+                                let definition = definition.clone();
+                                let definition_name = definition_name.to_owned();
+                                self.merge_patch_definition(
+                                    &definition_name,
+                                    definition,
+                                    in_progress,
+                                )
+                            }
+                            _ => property,
+                        }
+                    }
+                    None => self.relax(property, in_progress),
+                },
+                Schema::Bool(b) => {
+                    schema.object().properties.insert(name, Schema::Bool(b));
+                    continue;
+                }
+            };
+            schema.object().properties.insert(name, nullable(property));
+        }
+        schema
+    }
+
+    fn create_patch_request_body(schema: SchemaObject, content_type: &str) -> RefOr<RequestBody> {
+        let media = MediaType {
+            schema: Some(schema),
+            ..MediaType::default()
+        };
+        let mut request_body = RequestBody::default();
+        request_body.content.insert(content_type.to_owned(), media);
+        request_body.required = true;
+        request_body.into()
+    }
+
+    fn add_patch_operation<O: JsonSchema + Serialize, E: JsonSchema + Serialize>(
+        &mut self,
+        web_path: &ApiPath,
+        document_name: String,
+        operation_description: Option<String>,
+        request_body: RefOr<RequestBody>,
+        tests: &[Test],
+    ) {
+        let operation_id = format!("update{document_name}");
+        let method = http::Method::PATCH;
+
+        let mut resps = Responses::default();
+
+        let status = "200".to_owned();
+        let resp = self.create_response::<O>(document_name);
+        resps.responses.insert(status, resp.into());
+
+        self.add_error_responses::<E>(&mut resps);
+
+        let mut parameters: Vec<RefOr<Parameter>> = vec![];
+        self.add_path_params(web_path.clone(), &mut parameters);
+
+        let extensions = tests_extensions(&web_path.to_string(), tests);
+
+        self.generator.add_operation(OperationInfo {
+            path: web_path.to_string(),
+            method,
+            operation: Operation {
+                operation_id: Some(operation_id),
+                description: operation_description,
+                responses: resps,
+                request_body: Some(request_body),
+                parameters,
+                extensions,
+                ..Operation::default()
+            },
+        });
+    }
+}
+
+/// Marks `schema` as `nullable`.
+fn nullable(schema: SchemaObject) -> Schema {
+    let mut schema = wrap_ref(Schema::Object(schema));
+    schema
+        .extensions
+        .insert("nullable".to_owned(), Value::Bool(true));
+    Schema::Object(schema)
+}
+
+/// `schema` ready for sibling keywords: siblings of a `$ref` are ignored in
+/// OAS 3.0, so a `$ref` is moved into an `allOf`.
+pub(crate) fn wrap_ref(schema: Schema) -> SchemaObject {
+    let schema = schema.into_object();
+    if schema.is_ref() {
+        SchemaObject {
+            subschemas: Some(Box::new(SubschemaValidation {
+                all_of: Some(vec![Schema::Object(schema)]),
+                ..SubschemaValidation::default()
+            })),
+            ..SchemaObject::default()
+        }
+    } else {
+        schema
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{JSON_PATCH_CONTENT_TYPE, MERGE_PATCH_CONTENT_TYPE};
+    use crate::{ApiId, ApiPath, Oas3Builder};
+    use schemars::JsonSchema;
+    use serde::Serialize;
+    use serde_json::json;
+
+    #[derive(Serialize, JsonSchema)]
+    pub struct TestVenue {
+        pub name: String,
+    }
+
+    #[derive(Serialize, JsonSchema)]
+    pub struct TestEventForm {
+        pub title: String,
+        pub venue: TestVenue,
+    }
+
+    #[test]
+    fn test_merge_patch() {
+        let event_path = ApiPath::new(
+            Some("api".to_owned()),
+            vec![ApiId::new("events", "{eid}")],
+            None,
+        );
+        let mut oasb = Oas3Builder::default();
+        oasb.update_merge_patch::<TestEventForm, TestEventForm, String>(
+            &event_path,
+            "Events".to_owned(),
+            None,
+        );
+        let got = serde_json::to_value(oasb.build("1.0.0".to_owned())).unwrap();
+
+        let body = &got["paths"]["/api/events/{eid}"]["patch"]["requestBody"]["content"];
+        assert_eq!(
+            json!({"$ref": "#/components/schemas/TestEventFormPatch"}),
+            body[MERGE_PATCH_CONTENT_TYPE]["schema"]
+        );
+        assert_eq!(
+            json!({
+                "description": "JSON Merge Patch of TestEventForm, a `null` property is removed.",
+                "type": "object",
+                "properties": {
+                    "title": {"type": "string", "nullable": true},
+                    "venue": {
+                        "allOf": [{"$ref": "#/components/schemas/TestVenuePatch"}],
+                        "nullable": true
+                    }
+                }
+            }),
+            got["components"]["schemas"]["TestEventFormPatch"]
+        );
+        assert_eq!(
+            json!({
+                "description": "JSON Merge Patch of TestVenue, a `null` property is removed.",
+                "type": "object",
+                "properties": {"name": {"type": "string", "nullable": true}}
+            }),
+            got["components"]["schemas"]["TestVenuePatch"]
+        );
+        // the full form keeps its required properties
+        assert_eq!(
+            json!(["title", "venue"]),
+            got["components"]["schemas"]["TestEventForm"]["required"]
+        );
+    }
+
+    #[derive(Serialize, JsonSchema)]
+    #[schemars(rename = "TestEventFormPatch")]
+    pub struct TestEventFormDraft {
+        pub draft: bool,
+    }
+
+    #[test]
+    #[should_panic(expected = "is named `TestEventFormPatch` like a different schema")]
+    fn test_merge_patch_collision() {
+        let event_path = ApiPath::new(
+            Some("api".to_owned()),
+            vec![ApiId::new("events", "{eid}")],
+            None,
+        );
+        let draft_path = ApiPath::new(Some("api".to_owned()), vec![], Some("draft".to_owned()));
+        let mut oasb = Oas3Builder::default();
+        oasb.fetch::<TestEventFormDraft, String>(&draft_path, "Drafts".to_owned(), None);
+        oasb.update_merge_patch::<TestEventForm, TestEventForm, String>(
+            &event_path,
+            "Events".to_owned(),
+            None,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The schema `TestEventFormPatch` is named like a merge patch")]
+    fn test_merge_patch_collision_after() {
+        let event_path = ApiPath::new(
+            Some("api".to_owned()),
+            vec![ApiId::new("events", "{eid}")],
+            None,
+        );
+        let draft_path = ApiPath::new(Some("api".to_owned()), vec![], Some("draft".to_owned()));
+        let mut oasb = Oas3Builder::default();
+        oasb.update_merge_patch::<TestEventForm, TestEventForm, String>(
+            &event_path,
+            "Events".to_owned(),
+            None,
+        );
+        oasb.fetch::<TestEventFormDraft, String>(&draft_path, "Drafts".to_owned(), None);
+    }
+
+    #[test]
+    fn test_json_patch() {
+        let event_path = ApiPath::new(
+            Some("api".to_owned()),
+            vec![ApiId::new("events", "{eid}")],
+            None,
+        );
+        let mut oasb = Oas3Builder::default();
+        oasb.update_json_patch::<TestEventForm, String>(&event_path, "Events".to_owned(), None);
+        let got = serde_json::to_value(oasb.build("1.0.0".to_owned())).unwrap();
+        let _openapi_json: openapiv3::OpenAPI = serde_json::from_value(got.clone()).unwrap();
+
+        let body = &got["paths"]["/api/events/{eid}"]["patch"]["requestBody"]["content"];
+        assert_eq!(
+            json!({
+                "type": "array",
+                "items": {"$ref": "#/components/schemas/JsonPatchOperation"}
+            }),
+            body[JSON_PATCH_CONTENT_TYPE]["schema"]
+        );
+        assert_eq!(
+            json!(["op", "path"]),
+            got["components"]["schemas"]["JsonPatchOperation"]["required"]
+        );
+    }
+}
@@ -1,14 +1,83 @@
 use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::schema::{InstanceType, Schema, SchemaObject, SingleOrVec};
 use schemars::JsonSchema;
 use serde::Serialize;
 use serde_json::Value;
 
-use crate::generator::{Example, Map, Parameter, ParameterStyle, ParameterValue};
+use crate::constraints::{enum_variants, Constraints};
+use crate::generator::{Example, ExampleValue, Map, Parameter, ParameterStyle, ParameterValue};
+use crate::naming::subschema_for;
+
+/// Reasons a query parameter is rejected by [`QueryParamBuilder::try_build`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParameterError {
+    /// The style is not defined for parameters `in: query` (`matrix`, `label`, `simple`).
+    UnsupportedStyle { name: String, style: ParameterStyle },
+    /// The style requires a schema of a different type, e.g. `deepObject` on an array.
+    SchemaMismatch {
+        name: String,
+        style: ParameterStyle,
+        expected: InstanceType,
+        found: InstanceType,
+    },
+    /// The style does not define a serialization for this `explode` value.
+    InvalidExplode {
+        name: String,
+        style: ParameterStyle,
+        explode: bool,
+    },
+    /// The example, one of the examples or the default violates the value constraints.
+    ConstraintViolation {
+        name: String,
+        value: Value,
+        reason: String,
+    },
+}
+impl std::fmt::Display for ParameterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParameterError::UnsupportedStyle { name, style } => write!(
+                f,
+                "Query parameter `{name}` can not use style {style:?}, only form, spaceDelimited, pipeDelimited and deepObject are allowed in query"
+            ),
+            ParameterError::SchemaMismatch {
+                name,
+                style,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Query parameter `{name}` with style {style:?} must have a schema of type {expected:?}, found {found:?}"
+            ),
+            ParameterError::InvalidExplode {
+                name,
+                style,
+                explode,
+            } => write!(
+                f,
+                "Query parameter `{name}` with style {style:?} does not support explode: {explode}"
+            ),
+            ParameterError::ConstraintViolation {
+                name,
+                value,
+                reason,
+            } => write!(
+                f,
+                "Query parameter `{name}` value {value} violates its constraints: {reason}"
+            ),
+        }
+    }
+}
+impl std::error::Error for ParameterError {}
 
 #[derive(Debug, Clone)]
 pub struct QueryParamBuilder {
     param: Parameter,
     schema_generator: SchemaGenerator,
+    // Regenerates the schema of `T` with the generator of the `Oas3Builder` the
+    // parameter is registered with, so definitions end up in `components.schemas`.
+    schema_fn: fn(&mut SchemaGenerator) -> Schema,
+    constraints: Constraints,
 }
 impl QueryParamBuilder {
     #[must_use]
@@ -38,12 +107,178 @@ impl QueryParamBuilder {
         QueryParamBuilder {
             param,
             schema_generator,
+            schema_fn: subschema_for::<T>,
+            constraints: Constraints::default(),
         }
     }
 
+    /// A nested filter object serialized as `?filter[status]=open&filter[page]=2`.
+    /// Sets `style: deepObject` and `explode: true`, `T` must be a struct or map.
+    #[must_use]
+    pub fn deep_object<T: JsonSchema + Serialize>(name: String, example: Option<T>) -> Self {
+        Self::new::<T>(name, example)
+            .style(ParameterStyle::DeepObject)
+            .explode(true)
+    }
+
+    /// An array serialized as `?ids=1|2|3`.
+    /// Sets `style: pipeDelimited` and `explode: false`, `T` must be a sequence.
+    #[must_use]
+    pub fn pipe_delimited<T: JsonSchema + Serialize>(name: String, example: Option<T>) -> Self {
+        Self::new::<T>(name, example)
+            .style(ParameterStyle::PipeDelimited)
+            .explode(false)
+    }
+
+    /// An array serialized as `?ids=1%202%203`.
+    /// Sets `style: spaceDelimited` and `explode: false`, `T` must be a sequence.
+    #[must_use]
+    pub fn space_delimited<T: JsonSchema + Serialize>(name: String, example: Option<T>) -> Self {
+        Self::new::<T>(name, example)
+            .style(ParameterStyle::SpaceDelimited)
+            .explode(false)
+    }
+
+    /// # Panics
+    ///
+    /// Will panic if the `style`/`explode` combination is invalid, see `try_build`
     #[must_use]
     pub fn build(&self) -> Parameter {
-        self.param.clone()
+        match self.try_build() {
+            Ok(param) => param,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Validates the `style` and `explode` settings against the parameter schema.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the style is not defined for query parameters, if the
+    /// schema type does not match the style or if `explode` contradicts the style.
+    pub fn try_build(&self) -> Result<Parameter, ParameterError> {
+        let schema = (self.schema_fn)(&mut self.schema_generator.clone()).into();
+        self.validate(schema, &self.schema_generator)
+    }
+
+    /// Builds the parameter with its schema generated by `generator`, any definitions
+    /// the schema refers to are added to `generator`.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the `style`/`explode` combination is invalid, see `try_build`
+    pub(crate) fn build_with(&self, generator: &mut SchemaGenerator) -> Parameter {
+        let schema = (self.schema_fn)(generator).into();
+        match self.validate(schema, generator) {
+            Ok(param) => param,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    fn validate(
+        &self,
+        schema: SchemaObject,
+        generator: &SchemaGenerator,
+    ) -> Result<Parameter, ParameterError> {
+        let mut param = self.param.clone();
+        if let ParameterValue::Schema { schema: sc, .. } = &mut param.value {
+            *sc = self.constraints.apply(schema, generator);
+        }
+        self.check_values()?;
+        if let ParameterValue::Schema {
+            style: Some(style),
+            explode,
+            schema,
+            ..
+        } = &param.value
+        {
+            let name = param.name.clone();
+            let (expected, valid_explode) = match style {
+                ParameterStyle::Form => return Ok(param),
+                ParameterStyle::DeepObject => (InstanceType::Object, true),
+                ParameterStyle::SpaceDelimited | ParameterStyle::PipeDelimited => {
+                    (InstanceType::Array, false)
+                }
+                ParameterStyle::Matrix | ParameterStyle::Label | ParameterStyle::Simple => {
+                    return Err(ParameterError::UnsupportedStyle {
+                        name,
+                        style: style.clone(),
+                    });
+                }
+            };
+            if let Some(found) = Self::instance_type(generator, &Schema::Object(schema.clone())) {
+                if found != expected {
+                    return Err(ParameterError::SchemaMismatch {
+                        name,
+                        style: style.clone(),
+                        expected,
+                        found,
+                    });
+                }
+            }
+            // deepObject is only defined with explode, the delimited styles only without it;
+            // explode defaults to false for every style but form
+            let explode = explode.unwrap_or(false);
+            if explode != valid_explode {
+                return Err(ParameterError::InvalidExplode {
+                    name,
+                    style: style.clone(),
+                    explode,
+                });
+            }
+        }
+        Ok(param)
+    }
+
+    /// Checks the example, the examples and the default against the constraints.
+    fn check_values(&self) -> Result<(), ParameterError> {
+        let mut values: Vec<&Value> = vec![];
+        if let ParameterValue::Schema {
+            example, examples, ..
+        } = &self.param.value
+        {
+            values.extend(example);
+            for ex in examples.iter().flat_map(Map::values) {
+                if let ExampleValue::Value(value) = &ex.value {
+                    values.push(value);
+                }
+            }
+        }
+        values.extend(&self.constraints.default);
+        for value in values {
+            if let Err(reason) = self.constraints.check(value) {
+                return Err(ParameterError::ConstraintViolation {
+                    name: self.param.name.clone(),
+                    value: value.clone(),
+                    reason,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// The non null instance type of the schema, following `$ref`s into the
+    /// definitions. `None` if the schema does not declare exactly one type.
+    fn instance_type(generator: &SchemaGenerator, schema: &Schema) -> Option<InstanceType> {
+        let schema = if schema.is_ref() {
+            generator.dereference(schema)?
+        } else {
+            schema
+        };
+        let Schema::Object(schema) = schema else {
+            return None;
+        };
+        match &schema.instance_type {
+            Some(SingleOrVec::Single(it)) => Some(**it),
+            Some(SingleOrVec::Vec(its)) => {
+                let mut its = its.iter().filter(|it| InstanceType::Null.ne(*it));
+                match (its.next(), its.next()) {
+                    (Some(it), None) => Some(*it),
+                    _ => None,
+                }
+            }
+            None => None,
+        }
     }
 
     #[must_use]
@@ -203,4 +438,261 @@ impl QueryParamBuilder {
         }
         me
     }
+
+    /// Inclusive lower bound of a numeric parameter, or of its items if it is an array.
+    #[must_use]
+    pub fn minimum(&self, minimum: f64) -> Self {
+        let mut me = self.clone();
+        me.constraints.minimum = Some(minimum);
+        me
+    }
+
+    /// Inclusive upper bound of a numeric parameter, or of its items if it is an array.
+    #[must_use]
+    pub fn maximum(&self, maximum: f64) -> Self {
+        let mut me = self.clone();
+        me.constraints.maximum = Some(maximum);
+        me
+    }
+
+    #[must_use]
+    pub fn min_length(&self, min_length: u32) -> Self {
+        let mut me = self.clone();
+        me.constraints.min_length = Some(min_length);
+        me
+    }
+
+    #[must_use]
+    pub fn max_length(&self, max_length: u32) -> Self {
+        let mut me = self.clone();
+        me.constraints.max_length = Some(max_length);
+        me
+    }
+
+    /// Lower bound of the number of items of an array parameter.
+    #[must_use]
+    pub fn min_items(&self, min_items: u32) -> Self {
+        let mut me = self.clone();
+        me.constraints.min_items = Some(min_items);
+        me
+    }
+
+    /// Upper bound of the number of items of an array parameter.
+    #[must_use]
+    pub fn max_items(&self, max_items: u32) -> Self {
+        let mut me = self.clone();
+        me.constraints.max_items = Some(max_items);
+        me
+    }
+
+    /// An ECMA 262 regular expression string values must match. The examples and
+    /// the default are checked with the `regex` crate, which supports neither
+    /// look-arounds nor backreferences, patterns using them are not checked.
+    #[must_use]
+    pub fn pattern(&self, pattern: String) -> Self {
+        let mut me = self.clone();
+        me.constraints.pattern = Some(pattern);
+        me
+    }
+
+    /// Restricts the parameter to the serialized form of `values`.
+    #[must_use]
+    pub fn enum_values<V: Serialize>(&self, values: &[V]) -> Self {
+        let mut me = self.clone();
+        let values = values
+            .iter()
+            .map(|v| serde_json::to_value(v).unwrap_or_default())
+            .collect();
+        me.constraints.enum_values = Some(values);
+        me.constraints.enum_descriptions = vec![];
+        me
+    }
+
+    /// Restricts the parameter to the variants of the fieldless enum `V` and
+    /// emits the variant doc comments as `x-enum-descriptions`.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the schema of `V` is not an enumeration of values
+    #[must_use]
+    pub fn enum_variants<V: JsonSchema>(&self) -> Self {
+        let mut settings = SchemaSettings::openapi3();
+        settings.inline_subschemas = true;
+        let root = SchemaGenerator::new(settings).into_root_schema_for::<V>();
+        let (values, descriptions) = enum_variants(&root.schema)
+            .unwrap_or_else(|| panic!("{} is not a fieldless enum", V::schema_name()));
+        let mut me = self.clone();
+        me.constraints.enum_values = Some(values);
+        me.constraints.enum_descriptions = descriptions;
+        me
+    }
+
+    #[must_use]
+    pub fn default<V: Serialize>(&self, default: V) -> Self {
+        let mut me = self.clone();
+        me.constraints.default = Some(serde_json::to_value(&default).unwrap_or_default());
+        me
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ParameterError, QueryParamBuilder};
+    use crate::generator::ParameterStyle;
+    use schemars::schema::InstanceType;
+    use schemars::JsonSchema;
+    use serde::Serialize;
+    use serde_json::json;
+
+    #[derive(Serialize, JsonSchema)]
+    struct EventFilter {
+        status: String,
+        page: u32,
+    }
+
+    #[test]
+    fn test_deep_object() {
+        let param = QueryParamBuilder::deep_object::<EventFilter>(
+            "filter".to_owned(),
+            Some(EventFilter {
+                status: "open".to_owned(),
+                page: 2,
+            }),
+        )
+        .build();
+        let got = serde_json::to_value(param).unwrap();
+        assert_eq!(json!("deepObject"), got["style"]);
+        assert_eq!(json!(true), got["explode"]);
+        assert_eq!(json!({"status": "open", "page": 2}), got["example"]);
+    }
+
+    #[test]
+    fn test_delimited() {
+        let param = QueryParamBuilder::pipe_delimited::<Vec<u64>>("ids".to_owned(), None)
+            .try_build()
+            .unwrap();
+        let got = serde_json::to_value(param).unwrap();
+        assert_eq!(json!("pipeDelimited"), got["style"]);
+        assert_eq!(json!(false), got["explode"]);
+
+        let param = QueryParamBuilder::new::<Vec<u64>>("ids".to_owned(), None)
+            .style(ParameterStyle::PipeDelimited)
+            .try_build()
+            .unwrap();
+        let got = serde_json::to_value(param).unwrap();
+        assert_eq!(json!("pipeDelimited"), got["style"]);
+        assert_eq!(None, got.get("explode"));
+
+        let err = QueryParamBuilder::space_delimited::<String>("ids".to_owned(), None)
+            .try_build()
+            .unwrap_err();
+        assert_eq!(
+            ParameterError::SchemaMismatch {
+                name: "ids".to_owned(),
+                style: ParameterStyle::SpaceDelimited,
+                expected: InstanceType::Array,
+                found: InstanceType::String,
+            },
+            err
+        );
+    }
+
+    #[test]
+    fn test_invalid_combinations() {
+        let err = QueryParamBuilder::deep_object::<Vec<String>>("filter".to_owned(), None)
+            .try_build()
+            .unwrap_err();
+        assert!(matches!(err, ParameterError::SchemaMismatch { .. }));
+
+        let err = QueryParamBuilder::deep_object::<EventFilter>("filter".to_owned(), None)
+            .explode(false)
+            .try_build()
+            .unwrap_err();
+        assert!(matches!(err, ParameterError::InvalidExplode { .. }));
+
+        let err = QueryParamBuilder::new::<String>("title".to_owned(), None)
+            .style(ParameterStyle::Matrix)
+            .try_build()
+            .unwrap_err();
+        assert!(matches!(err, ParameterError::UnsupportedStyle { .. }));
+    }
+
+    #[test]
+    fn test_constraints() {
+        let param = QueryParamBuilder::new::<Vec<u64>>("limit".to_owned(), Some(vec![10]))
+            .minimum(1.0)
+            .maximum(100.0)
+            .default(20)
+            .build();
+        let got = serde_json::to_value(param).unwrap();
+        assert_eq!(
+            json!({
+                "type": "array",
+                "items": {"type": "integer", "format": "uint64", "minimum": 1.0, "maximum": 100.0},
+                "default": 20
+            }),
+            got["schema"]
+        );
+
+        let err = QueryParamBuilder::new::<String>("code".to_owned(), Some("abc".to_owned()))
+            .pattern("^[0-9]+$".to_owned())
+            .max_length(6)
+            .try_build()
+            .unwrap_err();
+        assert_eq!(
+            ParameterError::ConstraintViolation {
+                name: "code".to_owned(),
+                value: json!("abc"),
+                reason: "\"abc\" does not match the pattern `^[0-9]+$`".to_owned(),
+            },
+            err
+        );
+
+        // the `regex` crate can not check look-arounds
+        let param = QueryParamBuilder::new::<String>("role".to_owned(), Some("admin".to_owned()))
+            .pattern("^(?!admin).*$".to_owned())
+            .try_build();
+        assert!(param.is_ok());
+    }
+
+    #[test]
+    fn test_enum_variants() {
+        #[derive(Serialize, JsonSchema)]
+        #[serde(rename_all = "lowercase")]
+        #[allow(dead_code)]
+        enum EventStatus {
+            /// Not yet visible
+            Draft,
+            /// Visible to everyone
+            Published,
+        }
+
+        let param = QueryParamBuilder::new::<EventStatus>("status".to_owned(), None)
+            .enum_variants::<EventStatus>()
+            .example(json!("published"))
+            .build();
+        let got = serde_json::to_value(param).unwrap();
+        assert_eq!(
+            json!({
+                "type": "string",
+                "enum": ["draft", "published"],
+                "x-enum-descriptions": ["Not yet visible", "Visible to everyone"]
+            }),
+            got["schema"]
+        );
+
+        let err = QueryParamBuilder::new::<String>("status".to_owned(), Some("gone".to_owned()))
+            .enum_values(&["draft", "published"])
+            .try_build()
+            .unwrap_err();
+        assert!(matches!(err, ParameterError::ConstraintViolation { .. }));
+    }
+
+    #[test]
+    #[should_panic(expected = "does not support explode")]
+    fn test_build_panics_on_invalid() {
+        let _param = QueryParamBuilder::pipe_delimited::<Vec<u64>>("ids".to_owned(), None)
+            .explode(true)
+            .build();
+    }
 }
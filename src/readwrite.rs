@@ -0,0 +1,329 @@
+use std::collections::BTreeSet;
+
+use schemars::gen::SchemaGenerator;
+use schemars::schema::{Schema, SchemaObject};
+use schemars::visit::{visit_schema_object, Visitor};
+use schemars::JsonSchema;
+
+use crate::generator::{OpenApi, OpenApiGenerator, RefOr, RequestBody};
+use crate::naming::RenameRefs;
+use crate::oasgen::Oas3Builder;
+use crate::patch::wrap_ref;
+
+/// How properties that are only sent by the server (`readOnly`) or only by the
+/// client (`writeOnly`) are described.
+///
+/// Mark properties with `#[serde(skip_deserializing)]` / `#[serde(skip_serializing)]`,
+/// or without changing serde behaviour with
+/// `#[schemars(schema_with = "oas_gen::read_only::<T>")]` / `write_only::<T>`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ReadWriteMode {
+    /// One component per type, with `readOnly` / `writeOnly` property flags.
+    #[default]
+    Flags,
+    /// Components with flagged properties are split in two: `{Name}Input` without
+    /// the `readOnly` properties, referenced from request bodies, and `{Name}`
+    /// without the `writeOnly` properties, referenced from responses.
+    SplitComponents,
+}
+
+/// The schema of `T` flagged `readOnly`, for `#[schemars(schema_with = "oas_gen::read_only::<T>")]`.
+pub fn read_only<T: JsonSchema>(gen: &mut SchemaGenerator) -> Schema {
+    let mut schema = wrap_ref(gen.subschema_for::<T>());
+    schema.metadata().read_only = true;
+    Schema::Object(schema)
+}
+
+/// The schema of `T` flagged `writeOnly`, for `#[schemars(schema_with = "oas_gen::write_only::<T>")]`.
+pub fn write_only<T: JsonSchema>(gen: &mut SchemaGenerator) -> Schema {
+    let mut schema = wrap_ref(gen.subschema_for::<T>());
+    schema.metadata().write_only = true;
+    Schema::Object(schema)
+}
+
+impl Oas3Builder {
+    pub fn read_write_mode(&mut self, mode: ReadWriteMode) -> &mut Self {
+        self.read_write_mode = mode;
+        self
+    }
+}
+
+const INPUT_SUFFIX: &str = "Input";
+
+/// Applies `ReadWriteMode::SplitComponents` to the finished spec.
+pub(crate) fn split_components(openapi: &mut OpenApi) {
+    let Some(components) = &mut openapi.components else {
+        return;
+    };
+    let prefix = "#/components/schemas/";
+
+    // components with flagged properties, and the ones referencing them
+    let mut split: BTreeSet<String> = components
+        .schemas
+        .iter()
+        .filter(|(_, schema)| has_flagged_property(schema))
+        .map(|(name, _)| name.clone())
+        .collect();
+    loop {
+        let referencing: Vec<String> = components
+            .schemas
+            .iter()
+            .filter(|(name, _)| !split.contains(*name))
+            .filter(|(_, schema)| {
+                let mut refs = CollectRefs::default();
+                refs.visit_schema_object(&mut (*schema).clone());
+                refs.names(prefix).iter().any(|name| split.contains(name))
+            })
+            .map(|(name, _)| name.clone())
+            .collect();
+        if referencing.is_empty() {
+            break;
+        }
+        split.extend(referencing);
+    }
+
+    let mut to_input = RenameRefs {
+        prefix,
+        renames: split
+            .iter()
+            .map(|name| (name.clone(), format!("{name}{INPUT_SUFFIX}")))
+            .collect(),
+    };
+    for (name, input_name) in &to_input.renames {
+        assert!(
+            !components.schemas.contains_key(input_name),
+            "The input variant of `{}` is named `{}` like another schema, rename one with #[schemars(rename = \"..\")]",
+            name,
+            input_name
+        );
+    }
+    for name in &split {
+        let schema = components.schemas[name].clone();
+        let mut input = schema.clone();
+        retain_properties(&mut input, |meta| !meta.read_only);
+        to_input.visit_schema_object(&mut input);
+        components
+            .schemas
+            .insert(format!("{name}{INPUT_SUFFIX}"), input);
+
+        let mut output = schema;
+        retain_properties(&mut output, |meta| !meta.write_only);
+        components.schemas.insert(name.clone(), output);
+    }
+
+    let mut to_input_body = |request_body: &mut RefOr<RequestBody>| {
+        if let RefOr::Object(request_body) = request_body {
+            for media in request_body.content.values_mut() {
+                if let Some(schema) = &mut media.schema {
+                    to_input.visit_schema_object(schema);
+                }
+            }
+        }
+    };
+    components
+        .request_bodies
+        .values_mut()
+        .for_each(&mut to_input_body);
+    for path_item in openapi.paths.values_mut() {
+        for (_, op) in OpenApiGenerator::path_item_operations(path_item) {
+            op.request_body.iter_mut().for_each(&mut to_input_body);
+        }
+    }
+}
+
+fn has_flagged_property(schema: &SchemaObject) -> bool {
+    schema.object.as_ref().is_some_and(|object| {
+        object.properties.values().any(|property| {
+            let meta = property_metadata(property);
+            meta.read_only || meta.write_only
+        })
+    })
+}
+
+/// The metadata of a property, looking into the `allOf` wrapper of `read_only`/`write_only`.
+fn property_metadata(property: &Schema) -> schemars::schema::Metadata {
+    match property {
+        Schema::Object(obj) => obj.metadata.as_deref().cloned().unwrap_or_default(),
+        Schema::Bool(_) => schemars::schema::Metadata::default(),
+    }
+}
+
+/// Keeps the properties passing `keep` and clears the flags of the remaining ones.
+fn retain_properties<F: Fn(&schemars::schema::Metadata) -> bool>(
+    schema: &mut SchemaObject,
+    keep: F,
+) {
+    if let Some(object) = &mut schema.object {
+        let removed: Vec<String> = object
+            .properties
+            .iter()
+            .filter(|(_, property)| !keep(&property_metadata(property)))
+            .map(|(name, _)| name.clone())
+            .collect();
+        for name in &removed {
+            object.properties.remove(name);
+            object.required.remove(name);
+        }
+        for property in object.properties.values_mut() {
+            if let Schema::Object(obj) = property {
+                if let Some(meta) = &mut obj.metadata {
+                    meta.read_only = false;
+                    meta.write_only = false;
+                }
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct CollectRefs {
+    references: Vec<String>,
+}
+impl CollectRefs {
+    fn names(&self, prefix: &str) -> Vec<String> {
+        self.references
+            .iter()
+            .filter_map(|r| r.strip_prefix(prefix))
+            .map(ToOwned::to_owned)
+            .collect()
+    }
+}
+impl Visitor for CollectRefs {
+    fn visit_schema_object(&mut self, schema: &mut SchemaObject) {
+        if let Some(reference) = &schema.reference {
+            self.references.push(reference.clone());
+        }
+        visit_schema_object(self, schema);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReadWriteMode;
+    use crate::{ApiId, ApiPath, Oas3Builder};
+    use schemars::JsonSchema;
+    use serde::{Deserialize, Serialize};
+    use serde_json::json;
+
+    #[allow(dead_code)]
+    #[derive(Serialize, Deserialize, JsonSchema)]
+    pub struct TestEvent {
+        #[serde(skip_deserializing)]
+        pub id: String,
+        pub title: String,
+        #[serde(skip_serializing)]
+        pub secret: String,
+        #[schemars(schema_with = "crate::read_only::<TestVenue>")]
+        pub venue: TestVenue,
+    }
+
+    #[derive(Serialize, Deserialize, JsonSchema)]
+    pub struct TestVenue {
+        pub name: String,
+    }
+
+    #[derive(Serialize, Deserialize, JsonSchema)]
+    pub struct TestCollection {
+        pub events: Vec<TestEvent>,
+    }
+
+    fn build(mode: ReadWriteMode) -> serde_json::Value {
+        let events_path = ApiPath::new(Some("api".to_owned()), vec![], Some("events".to_owned()));
+        let event_path = ApiPath::new(
+            Some("api".to_owned()),
+            vec![ApiId::new("events", "{eid}")],
+            None,
+        );
+        let mut oasb = Oas3Builder::default();
+        oasb.read_write_mode(mode);
+        oasb.create::<TestEvent, TestEvent, String>(&events_path, "Events".to_owned(), None);
+        oasb.replace::<TestCollection, TestCollection, String>(
+            &event_path,
+            "Events".to_owned(),
+            None,
+        );
+        oasb.request_body_component::<TestEvent>("EventBody");
+        serde_json::to_value(oasb.build("1.0.0".to_owned())).unwrap()
+    }
+
+    #[test]
+    fn test_flags() {
+        let got = build(ReadWriteMode::Flags);
+        let event = &got["components"]["schemas"]["TestEvent"];
+        assert_eq!(json!(true), event["properties"]["id"]["readOnly"]);
+        assert_eq!(json!(true), event["properties"]["secret"]["writeOnly"]);
+        assert_eq!(
+            json!({"allOf": [{"$ref": "#/components/schemas/TestVenue"}], "readOnly": true}),
+            event["properties"]["venue"]
+        );
+        assert!(got["components"]["schemas"]["TestEventInput"].is_null());
+    }
+
+    #[test]
+    fn test_split_components() {
+        let got = build(ReadWriteMode::SplitComponents);
+        let _openapi_json: openapiv3::OpenAPI = serde_json::from_value(got.clone()).unwrap();
+        let schemas = &got["components"]["schemas"];
+        assert_eq!(
+            json!({
+                "type": "object",
+                "required": ["secret", "title"],
+                "properties": {"title": {"type": "string"}, "secret": {"type": "string"}}
+            }),
+            schemas["TestEventInput"]
+        );
+        let output_properties: Vec<&String> = schemas["TestEvent"]["properties"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .collect();
+        assert_eq!(vec!["id", "title", "venue"], output_properties);
+        assert_eq!(
+            json!({"$ref": "#/components/schemas/TestEventInput"}),
+            schemas["TestCollectionInput"]["properties"]["events"]["items"]
+        );
+        assert!(schemas["TestVenueInput"].is_null());
+
+        let json = "application/json; charset=utf-8";
+        let create = &got["paths"]["/api/events"]["post"];
+        assert_eq!(
+            json!({"$ref": "#/components/schemas/TestEventInput"}),
+            create["requestBody"]["content"][json]["schema"]
+        );
+        assert_eq!(
+            json!({"$ref": "#/components/schemas/TestEvent"}),
+            create["responses"]["201"]["content"][json]["schema"]
+        );
+        let replace = &got["paths"]["/api/events/{eid}"]["put"];
+        assert_eq!(
+            json!({"$ref": "#/components/schemas/TestCollectionInput"}),
+            replace["requestBody"]["content"][json]["schema"]
+        );
+        assert_eq!(
+            json!({"$ref": "#/components/schemas/TestEventInput"}),
+            got["components"]["requestBodies"]["EventBody"]["content"][json]["schema"]
+        );
+    }
+
+    #[derive(Serialize, Deserialize, JsonSchema)]
+    #[schemars(rename = "TestEventInput")]
+    pub struct TestEventDraft {
+        pub title: String,
+    }
+
+    #[test]
+    #[should_panic(expected = "is named `TestEventInput` like another schema")]
+    fn test_split_collision() {
+        let events_path = ApiPath::new(Some("api".to_owned()), vec![], Some("events".to_owned()));
+        let drafts_path = ApiPath::new(Some("api".to_owned()), vec![], Some("drafts".to_owned()));
+        let mut oasb = Oas3Builder::default();
+        oasb.read_write_mode(ReadWriteMode::SplitComponents);
+        oasb.create::<TestEvent, TestEvent, String>(&events_path, "Events".to_owned(), None);
+        oasb.create::<TestEventDraft, TestEventDraft, String>(
+            &drafts_path,
+            "Drafts".to_owned(),
+            None,
+        );
+        let _spec = oasb.build("1.0.0".to_owned());
+    }
+}
@@ -0,0 +1,278 @@
+use contracts::requires;
+use heck::SnakeCase;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::apipath::{ApiId, ApiPath};
+use crate::oasgen::Oas3Builder;
+use crate::queryparam::QueryParamBuilder;
+
+/// The operations `Oas3Builder::resource` can register for a `Resource`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Verb {
+    /// `GET` on the collection path
+    List,
+    /// `GET` on the item path
+    Fetch,
+    /// `POST` on the collection path
+    Create,
+    /// `PATCH` on the item path
+    Update,
+    /// `PUT` on the item path
+    Replace,
+    /// `DELETE` on the item path
+    Delete,
+}
+impl Verb {
+    pub const ALL: [Verb; 6] = [
+        Verb::List,
+        Verb::Fetch,
+        Verb::Create,
+        Verb::Update,
+        Verb::Replace,
+        Verb::Delete,
+    ];
+    pub const READ_ONLY: [Verb; 2] = [Verb::List, Verb::Fetch];
+
+    #[must_use]
+    pub fn method(self) -> http::Method {
+        match self {
+            Verb::List | Verb::Fetch => http::Method::GET,
+            Verb::Create => http::Method::POST,
+            Verb::Update => http::Method::PATCH,
+            Verb::Replace => http::Method::PUT,
+            Verb::Delete => http::Method::DELETE,
+        }
+    }
+
+    /// Whether the verb operates on the item path rather than the collection path.
+    #[must_use]
+    pub fn on_item(self) -> bool {
+        !matches!(self, Verb::List | Verb::Create)
+    }
+}
+
+/// A REST resource: a collection of documents identified by a key.
+/// ```
+/// use oas_gen::{Resource, Verb};
+/// # #[derive(serde::Serialize, schemars::JsonSchema)]
+/// # pub struct Event { title: String }
+/// # #[derive(serde::Serialize, schemars::JsonSchema)]
+/// # pub struct EventForm { title: String }
+/// struct Events;
+/// impl Resource for Events {
+///     type Form = EventForm;
+///     type Document = Event;
+///     type Collection = Vec<Event>;
+///     type Error = String;
+///     fn document_name() -> String {
+///         "Events".to_owned()
+///     }
+///     fn key() -> String {
+///         "{eid}".to_owned()
+///     }
+///     fn verbs() -> Vec<Verb> {
+///         Verb::READ_ONLY.to_vec()
+///     }
+/// }
+/// assert_eq!("events", Events::collection_name());
+/// ```
+pub trait Resource {
+    /// The request body of `create`, `update` and `replace`.
+    type Form: JsonSchema + Serialize;
+    /// The response body of `fetch`, `create`, `update`, `replace` and `delete`.
+    type Document: JsonSchema + Serialize;
+    /// The response body of `list`.
+    type Collection: JsonSchema + Serialize;
+    /// The body of the error responses.
+    type Error: JsonSchema + Serialize;
+
+    /// Used in the operation ids, e.g. `Events` gives `listEvents` and `fetchEvents`.
+    fn document_name() -> String;
+
+    /// The path parameter of the item path, e.g. `{eid}`.
+    fn key() -> String;
+
+    /// The path segment of the collection, the snake cased `document_name` by default.
+    #[must_use]
+    fn collection_name() -> String {
+        Self::document_name().to_snake_case()
+    }
+
+    /// The operations to register, all of them by default.
+    #[must_use]
+    fn verbs() -> Vec<Verb> {
+        Verb::ALL.to_vec()
+    }
+
+    /// Query parameters of the `list` operation.
+    #[must_use]
+    fn list_query_params() -> Vec<QueryParamBuilder> {
+        vec![]
+    }
+}
+
+impl Oas3Builder {
+    /// Registers the enabled `verbs` of `R` below `base_path`.
+    /// With `base_path` `/api/organizers/{oid}` and a resource named `events`,
+    /// `list` and `create` go to `/api/organizers/{oid}/events` while the
+    /// other operations go to `/api/organizers/{oid}/events/{eid}`.
+    #[requires(base_path.token.is_none())]
+    pub fn resource<R: Resource>(&mut self, base_path: &ApiPath) {
+        let (collection_path, item_path) = Self::resource_paths::<R>(base_path);
+        let document_name = R::document_name();
+        for verb in R::verbs() {
+            match verb {
+                Verb::List => {
+                    let list_path = ApiPath::with_queries(
+                        collection_path.prefix.clone(),
+                        collection_path.ids.clone(),
+                        collection_path.token.clone(),
+                        R::list_query_params(),
+                    );
+                    self.list::<R::Collection, R::Error>(&list_path, document_name.clone(), None);
+                }
+                Verb::Fetch => {
+                    self.fetch::<R::Document, R::Error>(&item_path, document_name.clone(), None);
+                }
+                Verb::Create => self.create::<R::Form, R::Document, R::Error>(
+                    &collection_path,
+                    document_name.clone(),
+                    None,
+                ),
+                Verb::Update => self.update::<R::Form, R::Document, R::Error>(
+                    &item_path,
+                    document_name.clone(),
+                    None,
+                ),
+                Verb::Replace => self.replace::<R::Form, R::Document, R::Error>(
+                    &item_path,
+                    document_name.clone(),
+                    None,
+                ),
+                Verb::Delete => self.delete_by_key::<R::Document, R::Error>(
+                    &item_path,
+                    document_name.clone(),
+                    None,
+                ),
+            }
+        }
+    }
+
+    /// The collection and the item path of `R` below `base_path`.
+    pub(crate) fn resource_paths<R: Resource>(base_path: &ApiPath) -> (ApiPath, ApiPath) {
+        let collection_path = ApiPath::new(
+            base_path.prefix.clone(),
+            base_path.ids.clone(),
+            Some(R::collection_name()),
+        );
+        let mut ids = base_path.ids.clone();
+        ids.push(ApiId::new(&R::collection_name(), &R::key()));
+        let item_path = ApiPath::new(base_path.prefix.clone(), ids, None);
+        (collection_path, item_path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Resource, Verb};
+    use crate::{ApiId, ApiPath, Oas3Builder};
+    use schemars::JsonSchema;
+    use serde::Serialize;
+
+    #[derive(Serialize, JsonSchema)]
+    pub struct TestEvent {
+        pub title: String,
+    }
+
+    #[derive(Serialize, JsonSchema)]
+    pub struct TestEventForm {
+        pub title: String,
+    }
+
+    struct Events;
+    impl Resource for Events {
+        type Form = TestEventForm;
+        type Document = TestEvent;
+        type Collection = Vec<TestEvent>;
+        type Error = String;
+        fn document_name() -> String {
+            "Events".to_owned()
+        }
+        fn key() -> String {
+            "{eid}".to_owned()
+        }
+    }
+
+    #[test]
+    fn test_resource_matches_manual_registration() {
+        let base_path = ApiPath::new(
+            Some("api".to_owned()),
+            vec![ApiId::new("organizers", "{oid}")],
+            None,
+        );
+        let mut oasb = Oas3Builder::default();
+        oasb.resource::<Events>(&base_path);
+        let got = oasb.build("1.0.0".to_owned());
+
+        let collection_path = ApiPath::new(
+            Some("api".to_owned()),
+            vec![ApiId::new("organizers", "{oid}")],
+            Some("events".to_owned()),
+        );
+        let item_path = ApiPath::new(
+            Some("api".to_owned()),
+            vec![
+                ApiId::new("organizers", "{oid}"),
+                ApiId::new("events", "{eid}"),
+            ],
+            None,
+        );
+        let document_name = "Events".to_owned();
+        let mut oasb = Oas3Builder::default();
+        oasb.list::<Vec<TestEvent>, String>(&collection_path, document_name.clone(), None);
+        oasb.fetch::<TestEvent, String>(&item_path, document_name.clone(), None);
+        oasb.create::<TestEventForm, TestEvent, String>(
+            &collection_path,
+            document_name.clone(),
+            None,
+        );
+        oasb.update::<TestEventForm, TestEvent, String>(&item_path, document_name.clone(), None);
+        oasb.replace::<TestEventForm, TestEvent, String>(&item_path, document_name.clone(), None);
+        oasb.delete_by_key::<TestEvent, String>(&item_path, document_name, None);
+        let expect = oasb.build("1.0.0".to_owned());
+
+        similar_asserts::assert_eq!(expect, got);
+    }
+
+    #[test]
+    fn test_resource_verbs() {
+        struct ReadOnlyEvents;
+        impl Resource for ReadOnlyEvents {
+            type Form = ();
+            type Document = TestEvent;
+            type Collection = Vec<TestEvent>;
+            type Error = String;
+            fn document_name() -> String {
+                "Events".to_owned()
+            }
+            fn key() -> String {
+                "{eid}".to_owned()
+            }
+            fn verbs() -> Vec<Verb> {
+                Verb::READ_ONLY.to_vec()
+            }
+        }
+
+        let mut oasb = Oas3Builder::default();
+        oasb.resource::<ReadOnlyEvents>(&ApiPath::new(Some("api".to_owned()), vec![], None));
+        let got = oasb.build("1.0.0".to_owned());
+
+        let paths: Vec<&String> = got.paths.keys().collect();
+        assert_eq!(vec!["/api/events", "/api/events/{eid}"], paths);
+        let collection = &got.paths["/api/events"];
+        assert!(collection.get.is_some() && collection.post.is_none());
+        let item = &got.paths["/api/events/{eid}"];
+        assert!(item.get.is_some() && item.patch.is_none() && item.delete.is_none());
+    }
+}
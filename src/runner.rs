@@ -0,0 +1,784 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::path::Path;
+use std::time::Duration;
+
+use schemars::schema::SchemaObject;
+use serde_json::Value;
+
+use crate::generator::{OpenApi, Operation, RefOr};
+use crate::validation::SchemaValidator;
+use crate::xtests::{
+    capture, operation_of, order_tests, substitute, substitute_value, tested_operations, Hook,
+    Test, TestOrderError, TestedOperation,
+};
+
+/// Runs the `x-tests` of a spec against a server, e.g. one spawned by the test.
+///
+/// Only plain `http://` base URLs are supported.
+/// ```no_run
+/// # use oas_gen::{Oas3Builder, TestRunner};
+/// # let openapi = Oas3Builder::default().build("1.0.0".to_owned());
+/// let report = TestRunner::new("http://127.0.0.1:8080")
+///     .token("eyJhbGciOi...")
+///     .run(&openapi)
+///     .unwrap();
+/// report.assert_success();
+/// ```
+#[derive(Debug, Clone)]
+pub struct TestRunner {
+    base_url: String,
+    token: Option<String>,
+    timeout: Duration,
+}
+
+/// The results of `TestRunner::run`, in running order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TestReport {
+    pub results: Vec<TestResult>,
+}
+
+/// The result of a single test.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestResult {
+    /// The operation, e.g. `GET /api/events/{eid}`.
+    pub operation: String,
+    pub description: String,
+    pub outcome: TestOutcome,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TestOutcome {
+    Passed,
+    /// The test ran but some checks failed, or could not run, with the reasons.
+    Failed(Vec<String>),
+    /// The test is marked `skip`.
+    Skipped,
+}
+
+/// A response received by the runner.
+#[derive(Debug, Clone)]
+struct HttpResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl TestRunner {
+    /// A runner sending requests to `base_url`, e.g. `http://127.0.0.1:8080`,
+    /// which may end with a path prefixed to every operation path.
+    #[must_use]
+    pub fn new(base_url: &str) -> Self {
+        TestRunner {
+            base_url: base_url.trim_end_matches('/').to_owned(),
+            token: None,
+            timeout: Duration::from_secs(10),
+        }
+    }
+
+    /// The JWT sent as a bearer token by the tests with `auth`.
+    #[must_use]
+    pub fn token(mut self, token: &str) -> Self {
+        self.token = Some(token.to_owned());
+        self
+    }
+
+    /// The read and write timeout of every request, defaults to 10 seconds.
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Runs the `x-tests` of the spec stored as JSON at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the file can not be read or parsed, or the tests can not be ordered.
+    pub fn run_file<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Result<TestReport, Box<dyn std::error::Error>> {
+        let json = std::fs::read_to_string(path)?;
+        let openapi: OpenApi = serde_json::from_str(&json)?;
+        Ok(self.run(&openapi)?)
+    }
+
+    /// Runs the `x-tests` of every operation, see `xtests::order_tests` for the order.
+    /// Variables captured by a test are available to the tests after it; a test
+    /// depending on a failed test fails without running.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the dependencies between the tests can not be satisfied.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if an `x-tests` extension is not a list of tests
+    pub fn run(&self, openapi: &OpenApi) -> Result<TestReport, TestOrderError> {
+        let operations = tested_operations(openapi);
+        let ordered = order_tests(operations.iter().flat_map(|op| &op.tests))?;
+
+        let validator = SchemaValidator::for_spec(openapi);
+        let mut variables = BTreeMap::new();
+        let mut failed_ids: Vec<&str> = vec![];
+        let mut report = TestReport::default();
+        for test in ordered {
+            let TestedOperation {
+                path,
+                method,
+                operation: op,
+                ..
+            } = operation_of(&operations, test);
+            let outcome = if test.skip {
+                TestOutcome::Skipped
+            } else if let Some(dependency) = test
+                .depends_on
+                .iter()
+                .find(|d| failed_ids.contains(&d.as_str()))
+            {
+                TestOutcome::Failed(vec![format!("The test `{dependency}` failed")])
+            } else {
+                let errors =
+                    self.run_test(openapi, &validator, path, method, op, test, &mut variables);
+                if errors.is_empty() {
+                    TestOutcome::Passed
+                } else {
+                    TestOutcome::Failed(errors)
+                }
+            };
+            if matches!(outcome, TestOutcome::Failed(_)) && !test.id.is_empty() {
+                failed_ids.push(&test.id);
+            }
+            report.results.push(TestResult {
+                operation: format!("{method} {path}"),
+                description: test.description.clone(),
+                outcome,
+            });
+        }
+        Ok(report)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn run_test(
+        &self,
+        openapi: &OpenApi,
+        validator: &SchemaValidator<'_>,
+        path: &str,
+        method: &http::Method,
+        op: &Operation,
+        test: &Test,
+        variables: &mut BTreeMap<String, String>,
+    ) -> Vec<String> {
+        let mut errors = vec![];
+        for hook in &test.setup {
+            if let Err(e) = self.run_hook(hook, variables) {
+                errors.push(format!("setup {} {}: {}", hook.method, hook.path, e));
+            }
+        }
+        if errors.is_empty() {
+            let hook = Hook {
+                method: method.to_string(),
+                path: path.to_owned(),
+                auth: test.auth,
+                params: test.params.clone(),
+                request: test.request.clone(),
+                capture: BTreeMap::new(),
+            };
+            match self.send(&hook, variables) {
+                Ok(response) => {
+                    check_response(
+                        openapi,
+                        validator,
+                        op,
+                        test,
+                        &response,
+                        variables,
+                        &mut errors,
+                    );
+                }
+                Err(e) => errors.push(format!("request failed: {e}")),
+            }
+        }
+        for hook in &test.teardown {
+            if let Err(e) = self.run_hook(hook, variables) {
+                errors.push(format!("teardown {} {}: {}", hook.method, hook.path, e));
+            }
+        }
+        errors
+    }
+
+    fn run_hook(
+        &self,
+        hook: &Hook,
+        variables: &mut BTreeMap<String, String>,
+    ) -> Result<(), String> {
+        let response = self.send(hook, variables).map_err(|e| e.to_string())?;
+        if !(200..300).contains(&response.status) {
+            return Err(format!("responded {}", response.status));
+        }
+        capture_variables(&hook.capture, &response, variables)
+    }
+
+    fn send(&self, hook: &Hook, variables: &BTreeMap<String, String>) -> io::Result<HttpResponse> {
+        let path = target(hook, variables);
+
+        let mut headers: Vec<(String, String)> = hook
+            .request
+            .headers
+            .iter()
+            .map(|(k, v)| (k.clone(), substitute(v, variables)))
+            .collect();
+        if hook.auth {
+            if let Some(token) = &self.token {
+                headers.push(("Authorization".to_owned(), format!("Bearer {token}")));
+            }
+        }
+        if !hook.request.cookie.is_empty() {
+            headers.push((
+                "Cookie".to_owned(),
+                substitute(&hook.request.cookie, variables),
+            ));
+        }
+        let body = if hook.request.body.is_null() {
+            None
+        } else {
+            headers.push(("Content-Type".to_owned(), "application/json".to_owned()));
+            let body = substitute_value(&hook.request.body, variables);
+            Some(serde_json::to_vec(&body).map_err(io::Error::other)?)
+        };
+        self.request(&hook.method, &path, &headers, body.as_deref())
+    }
+
+    /// Sends an HTTP/1.1 request, closing the connection after the response.
+    fn request(
+        &self,
+        method: &str,
+        path: &str,
+        headers: &[(String, String)],
+        body: Option<&[u8]>,
+    ) -> io::Result<HttpResponse> {
+        let invalid =
+            |message: &str| io::Error::new(io::ErrorKind::InvalidInput, message.to_owned());
+        let rest = self
+            .base_url
+            .strip_prefix("http://")
+            .ok_or_else(|| invalid("only http:// base URLs are supported"))?;
+        let (authority, base_path) = rest.find('/').map_or((rest, ""), |i| rest.split_at(i));
+
+        let mut stream = TcpStream::connect(authority)?;
+        stream.set_read_timeout(Some(self.timeout))?;
+        stream.set_write_timeout(Some(self.timeout))?;
+        let body = body.unwrap_or_default();
+        let headers = headers
+            .iter()
+            .fold(String::new(), |mut lines, (name, value)| {
+                let _ = write!(lines, "{name}: {value}\r\n");
+                lines
+            });
+        let request = format!(
+            "{method} {base_path}{path} HTTP/1.1\r\nHost: {authority}\r\nConnection: close\r\nAccept: application/json\r\n{headers}Content-Length: {}\r\n\r\n",
+            body.len()
+        );
+        stream.write_all(request.as_bytes())?;
+        stream.write_all(body)?;
+
+        let mut raw = vec![];
+        stream.read_to_end(&mut raw)?;
+        parse_response(&raw).ok_or_else(|| invalid("malformed HTTP response"))
+    }
+}
+
+fn parse_response(raw: &[u8]) -> Option<HttpResponse> {
+    let end = raw.windows(4).position(|w| w == b"\r\n\r\n")?;
+    let head = std::str::from_utf8(&raw[..end]).ok()?;
+    let mut lines = head.split("\r\n");
+    let status = lines.next()?.split(' ').nth(1)?.parse().ok()?;
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_owned()))
+        .collect();
+    let mut body = raw[end + 4..].to_vec();
+    let chunked = headers
+        .iter()
+        .any(|(name, value)| name == "transfer-encoding" && value.eq_ignore_ascii_case("chunked"));
+    if chunked {
+        body = decode_chunked(&body)?;
+    }
+    Some(HttpResponse {
+        status,
+        headers,
+        body,
+    })
+}
+
+fn decode_chunked(mut raw: &[u8]) -> Option<Vec<u8>> {
+    let mut body = vec![];
+    loop {
+        let line_end = raw.windows(2).position(|w| w == b"\r\n")?;
+        let size = std::str::from_utf8(&raw[..line_end]).ok()?;
+        let size = usize::from_str_radix(size.split(';').next()?.trim(), 16).ok()?;
+        if size == 0 {
+            return Some(body);
+        }
+        let chunk = raw.get(line_end + 2..line_end + 2 + size)?;
+        body.extend_from_slice(chunk);
+        raw = raw.get(line_end + 4 + size..)?;
+    }
+}
+
+/// The path and query of the request of `hook`, with the params and captured
+/// variables substituted and percent encoded.
+fn target(hook: &Hook, variables: &BTreeMap<String, String>) -> String {
+    let mut path = hook.path.clone();
+    for (name, value) in &hook.params {
+        let value = substitute(value, variables);
+        path = path.replace(&format!("{{{name}}}"), &encode(&value));
+    }
+    let encoded: BTreeMap<String, String> = variables
+        .iter()
+        .map(|(name, value)| (name.clone(), encode(value)))
+        .collect();
+    path = substitute(&path, &encoded);
+    let query: Vec<String> = hook
+        .request
+        .query
+        .iter()
+        .map(|(k, v)| format!("{}={}", encode(k), encode(&substitute(v, variables))))
+        .collect();
+    if !query.is_empty() {
+        path = format!("{}?{}", path, query.join("&"));
+    }
+    path
+}
+
+/// Percent encodes everything but the unreserved characters.
+pub(crate) fn encode(text: &str) -> String {
+    text.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+/// Percent encodes a path param but for its `{name}` placeholders of variables,
+/// the braces of any other text are encoded too.
+pub(crate) fn encode_param(value: &str, is_variable: impl Fn(&str) -> bool) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some((start, end)) = rest
+        .find('{')
+        .and_then(|start| Some((start, start + rest[start..].find('}')?)))
+    {
+        if is_variable(&rest[start + 1..end]) {
+            encoded.push_str(&encode(&rest[..start]));
+            encoded.push_str(&rest[start..=end]);
+        } else {
+            encoded.push_str(&encode(&rest[..=end]));
+        }
+        rest = &rest[end + 1..];
+    }
+    encoded.push_str(&encode(rest));
+    encoded
+}
+
+fn check_response(
+    openapi: &OpenApi,
+    validator: &SchemaValidator<'_>,
+    op: &Operation,
+    test: &Test,
+    response: &HttpResponse,
+    variables: &mut BTreeMap<String, String>,
+    errors: &mut Vec<String>,
+) {
+    let expected = &test.response;
+    if !expected.status.is_empty() && !expected.status.contains(&response.status) {
+        errors.push(format!(
+            "expected the status {:?}, got {}",
+            expected.status, response.status
+        ));
+    }
+    for (name, value) in &expected.headers {
+        let value = substitute(value, variables);
+        let name = name.to_lowercase();
+        match response.headers.iter().find(|(n, _)| *n == name) {
+            Some((_, actual)) if *actual == value => {}
+            Some((_, actual)) => errors.push(format!(
+                "expected the header `{name}: {value}`, got `{actual}`"
+            )),
+            None => errors.push(format!("expected the header `{name}`, got nothing")),
+        }
+    }
+    for (name, value) in &expected.cookie {
+        let value = substitute(value, variables);
+        let found = response
+            .headers
+            .iter()
+            .filter(|(n, _)| n == "set-cookie")
+            .filter_map(|(_, cookie)| cookie.split(';').next()?.split_once('='))
+            .any(|(n, v)| n.trim() == name && v.trim() == value);
+        if !found {
+            errors.push(format!("expected the cookie `{name}={value}`"));
+        }
+    }
+
+    if expected.body.is_empty() && test.required.is_empty() && test.capture.is_empty() {
+        return;
+    }
+    let body: Value = match serde_json::from_slice(&response.body) {
+        Ok(body) => body,
+        Err(e) => {
+            errors.push(format!("the body is not JSON: {e}"));
+            return;
+        }
+    };
+    for name in &test.required {
+        if body.get(name).is_none() {
+            errors.push(format!("expected the property `{name}` in the body"));
+        }
+    }
+    let documented = documented_schema(openapi, op, response.status);
+    for assertion in &expected.body {
+        if let Err(e) = assertion.check(&body, documented, validator) {
+            errors.push(e);
+        }
+    }
+    if let Err(e) = capture_variables(&test.capture, response, variables) {
+        errors.push(e);
+    }
+}
+
+fn capture_variables(
+    captures: &BTreeMap<String, String>,
+    response: &HttpResponse,
+    variables: &mut BTreeMap<String, String>,
+) -> Result<(), String> {
+    if captures.is_empty() {
+        return Ok(());
+    }
+    let body: Value =
+        serde_json::from_slice(&response.body).map_err(|e| format!("the body is not JSON: {e}"))?;
+    for (name, path) in captures {
+        let value = capture(&body, path).ok_or_else(|| format!("nothing to capture at {path}"))?;
+        let value = match value {
+            Value::String(text) => text.clone(),
+            other => other.to_string(),
+        };
+        variables.insert(name.clone(), value);
+    }
+    Ok(())
+}
+
+/// The schema of the `status` response of `op`, resolving a response component.
+fn documented_schema<'a>(
+    openapi: &'a OpenApi,
+    op: &'a Operation,
+    status: u16,
+) -> Option<&'a SchemaObject> {
+    let response = op
+        .responses
+        .responses
+        .get(&status.to_string())
+        .or(op.responses.default.as_ref())?;
+    let response = match response {
+        RefOr::Object(response) => response,
+        RefOr::Ref(reference) => {
+            let name = reference.reference.rsplit('/').next()?;
+            match openapi.components.as_ref()?.responses.get(name)? {
+                RefOr::Object(response) => response,
+                RefOr::Ref(_) => return None,
+            }
+        }
+    };
+    response
+        .content
+        .values()
+        .find_map(|media| media.schema.as_ref())
+}
+
+impl TestReport {
+    #[must_use]
+    pub fn passed(&self) -> usize {
+        self.count(|outcome| *outcome == TestOutcome::Passed)
+    }
+
+    #[must_use]
+    pub fn failed(&self) -> usize {
+        self.count(|outcome| matches!(outcome, TestOutcome::Failed(_)))
+    }
+
+    #[must_use]
+    pub fn skipped(&self) -> usize {
+        self.count(|outcome| *outcome == TestOutcome::Skipped)
+    }
+
+    #[must_use]
+    pub fn is_success(&self) -> bool {
+        self.failed() == 0
+    }
+
+    /// # Panics
+    ///
+    /// Will panic with the summary if a test failed, e.g. to fail a `cargo test`
+    pub fn assert_success(&self) {
+        assert!(self.is_success(), "{}", self);
+    }
+
+    fn count(&self, pred: impl Fn(&TestOutcome) -> bool) -> usize {
+        self.results.iter().filter(|r| pred(&r.outcome)).count()
+    }
+}
+
+impl std::fmt::Display for TestReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for result in &self.results {
+            let status = match &result.outcome {
+                TestOutcome::Passed => "ok",
+                TestOutcome::Failed(_) => "FAILED",
+                TestOutcome::Skipped => "ignored",
+            };
+            writeln!(
+                f,
+                "test {} {} ... {}",
+                result.operation, result.description, status
+            )?;
+            if let TestOutcome::Failed(errors) = &result.outcome {
+                for error in errors {
+                    writeln!(f, "    {error}")?;
+                }
+            }
+        }
+        write!(
+            f,
+            "\nx-tests result: {}. {} passed; {} failed; {} ignored",
+            if self.is_success() { "ok" } else { "FAILED" },
+            self.passed(),
+            self.failed(),
+            self.skipped()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{target, TestOutcome, TestRunner};
+    use crate::xtests::{BodyAssertion, Hook, Response, Test};
+    use crate::{ApiId, ApiPath, Oas3Builder};
+    use schemars::JsonSchema;
+    use serde::Serialize;
+    use serde_json::json;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+
+    #[derive(Serialize, JsonSchema)]
+    pub struct TestEvent {
+        pub id: String,
+        pub title: String,
+    }
+
+    /// Answers `POST /api/events` with a new event and `GET /api/events/e1` with it,
+    /// using a chunked body for the latter.
+    fn spawn_server() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line == "\r\n" {
+                        break;
+                    }
+                    if let Some(length) = line.to_lowercase().strip_prefix("content-length:") {
+                        content_length = length.trim().parse().unwrap();
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+
+                let event = r#"{"id":"e1","title":"Hackaton"}"#;
+                let response = if request_line.starts_with("POST /api/events ") {
+                    format!(
+                        "HTTP/1.1 201 Created\r\nContent-Length: {}\r\nSet-Cookie: session=abc; Path=/\r\n\r\n{}",
+                        event.len(),
+                        event
+                    )
+                } else if request_line.starts_with("GET /api/events/e1 ") {
+                    format!(
+                        "HTTP/1.1 201 OK\r\nTransfer-Encoding: chunked\r\n\r\n{:x}\r\n{}\r\n0\r\n\r\n",
+                        event.len(),
+                        event
+                    )
+                } else {
+                    "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n".to_owned()
+                };
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        format!("http://{address}")
+    }
+
+    #[test]
+    fn test_run() {
+        let mut capture = std::collections::BTreeMap::default();
+        capture.insert("eid".to_owned(), "$.id".to_owned());
+        let mut cookie = std::collections::BTreeMap::default();
+        cookie.insert("session".to_owned(), "abc".to_owned());
+        let create = Test {
+            description: "Create an event.".to_owned(),
+            id: "create".to_owned(),
+            capture,
+            request: crate::xtests::Request {
+                body: json!({"title": "Hackaton"}),
+                ..crate::xtests::Request::default()
+            },
+            response: Response {
+                status: vec![201],
+                cookie,
+                body: vec![BodyAssertion::MatchesSchema],
+                ..Response::default()
+            },
+            ..Test::default()
+        };
+        let mut params = std::collections::BTreeMap::default();
+        params.insert("eid".to_owned(), "{eid}".to_owned());
+        let fetch = Test {
+            description: "Fetch the created event.".to_owned(),
+            depends_on: vec!["create".to_owned()],
+            params,
+            response: Response {
+                status: vec![201],
+                body: vec![BodyAssertion::PointerEquals(
+                    "/title".to_owned(),
+                    json!("Hackaton"),
+                )],
+                ..Response::default()
+            },
+            ..Test::default()
+        };
+        let mut params = std::collections::BTreeMap::default();
+        params.insert("eid".to_owned(), "e2".to_owned());
+        let missing = Test {
+            description: "Fetch a missing event.".to_owned(),
+            params,
+            response: Response {
+                status: vec![201],
+                ..Response::default()
+            },
+            ..Test::default()
+        };
+
+        let events_path = ApiPath::new(Some("api".to_owned()), vec![], Some("events".to_owned()));
+        let event_path = ApiPath::new(
+            Some("api".to_owned()),
+            vec![ApiId::new("events", "{eid}")],
+            None,
+        );
+        let mut oasb = Oas3Builder::default();
+        // the fetch is added first, its dependency still runs the create before it
+        oasb.fetch_with_tests::<TestEvent, String>(
+            &event_path,
+            "Events".to_owned(),
+            None,
+            &[fetch, missing],
+        );
+        oasb.create_with_tests::<TestEvent, TestEvent, String>(
+            &events_path,
+            "Events".to_owned(),
+            None,
+            &[create],
+        );
+        let openapi = oasb.build("1.0.0".to_owned());
+
+        let report = TestRunner::new(&spawn_server()).run(&openapi).unwrap();
+        let descriptions: Vec<_> = report
+            .results
+            .iter()
+            .map(|r| r.description.as_str())
+            .collect();
+        assert_eq!(
+            vec![
+                "Create an event.",
+                "Fetch the created event.",
+                "Fetch a missing event."
+            ],
+            descriptions
+        );
+        assert_eq!(2, report.passed(), "{report}");
+        assert_eq!(
+            TestOutcome::Failed(vec!["expected the status [201], got 404".to_owned()]),
+            report.results[2].outcome
+        );
+        assert!(report
+            .to_string()
+            .ends_with("2 passed; 1 failed; 0 ignored"));
+    }
+
+    #[test]
+    fn test_no_components() {
+        let mut params = std::collections::BTreeMap::default();
+        params.insert("eid".to_owned(), "e1".to_owned());
+        let fetch = Test {
+            description: "Fetch an event.".to_owned(),
+            params,
+            response: Response {
+                status: vec![201],
+                ..Response::default()
+            },
+            ..Test::default()
+        };
+        let event_path = ApiPath::new(
+            Some("api".to_owned()),
+            vec![ApiId::new("events", "{eid}")],
+            None,
+        );
+        let mut oasb = Oas3Builder::default();
+        oasb.fetch_with_tests::<TestEvent, String>(
+            &event_path,
+            "Events".to_owned(),
+            None,
+            &[fetch],
+        );
+        let mut openapi = oasb.build("1.0.0".to_owned());
+        openapi.components = None;
+
+        let report = TestRunner::new(&spawn_server()).run(&openapi).unwrap();
+        assert_eq!(1, report.passed(), "{report}");
+    }
+
+    #[test]
+    fn test_target() {
+        let mut params = std::collections::BTreeMap::default();
+        params.insert("eid".to_owned(), "{eid}".to_owned());
+        let mut query = std::collections::BTreeMap::default();
+        query.insert("q".to_owned(), "{title}".to_owned());
+        let hook = Hook {
+            method: "GET".to_owned(),
+            path: "/api/events/{eid}/tickets/{tid}".to_owned(),
+            params,
+            request: crate::xtests::Request {
+                query,
+                ..crate::xtests::Request::default()
+            },
+            ..Hook::default()
+        };
+        let mut variables = std::collections::BTreeMap::default();
+        variables.insert("eid".to_owned(), "2024/e 1".to_owned());
+        variables.insert("tid".to_owned(), "t?1".to_owned());
+        variables.insert("title".to_owned(), "a&b".to_owned());
+        assert_eq!(
+            "/api/events/2024%2Fe%201/tickets/t%3F1?q=a%26b",
+            target(&hook, &variables)
+        );
+    }
+}
@@ -0,0 +1,244 @@
+use contracts::requires;
+
+use crate::apipath::{ApiId, ApiPath};
+use crate::generator::{Map, Operation};
+use crate::oasgen::Oas3Builder;
+use crate::resource::Resource;
+
+/// A position in the resource tree, see `Oas3Builder::scope`.
+///
+/// Tags, security requirements and error responses set on a scope apply to every
+/// operation registered in it and in its nested scopes.
+#[derive(Debug)]
+pub struct Scope<'a> {
+    builder: &'a mut Oas3Builder,
+    prefix: Option<String>,
+    ids: Vec<ApiId>,
+    tags: Vec<String>,
+    security: Option<Vec<Map<String, Vec<String>>>>,
+    errors: Vec<(u16, String)>,
+}
+
+impl Oas3Builder {
+    /// Starts a resource tree below `/prefix`.
+    /// ```
+    /// # use oas_gen::{Oas3Builder, Resource};
+    /// # #[derive(serde::Serialize, schemars::JsonSchema)]
+    /// # pub struct Doc { title: String }
+    /// # struct Organizers;
+    /// # impl Resource for Organizers {
+    /// #     type Form = Doc; type Document = Doc; type Collection = Vec<Doc>; type Error = String;
+    /// #     fn document_name() -> String { "Organizers".to_owned() }
+    /// #     fn key() -> String { "{oid}".to_owned() }
+    /// # }
+    /// # struct Events;
+    /// # impl Resource for Events {
+    /// #     type Form = Doc; type Document = Doc; type Collection = Vec<Doc>; type Error = String;
+    /// #     fn document_name() -> String { "Events".to_owned() }
+    /// #     fn key() -> String { "{eid}".to_owned() }
+    /// # }
+    /// let mut oasb = Oas3Builder::default();
+    /// oasb.scope("api")
+    ///     .security("bearerAuth", &[])
+    ///     .error(404, "Not Found")
+    ///     .resource::<Organizers, _>(|organizers| {
+    ///         organizers.tag("Events").resource::<Events, _>(|_| {});
+    ///     });
+    /// let spec = oasb.build("1.0.0".to_owned());
+    /// assert!(spec.paths.contains_key("/api/organizers/{oid}/events/{eid}"));
+    /// ```
+    #[requires(!prefix.starts_with('/'))]
+    #[requires(!prefix.contains('{'))]
+    #[requires(!prefix.contains('}'))]
+    pub fn scope(&mut self, prefix: &str) -> Scope<'_> {
+        let prefix = if prefix.is_empty() {
+            None
+        } else {
+            Some(prefix.to_owned())
+        };
+        Scope {
+            builder: self,
+            prefix,
+            ids: vec![],
+            tags: vec![],
+            security: None,
+            errors: vec![],
+        }
+    }
+}
+
+impl Scope<'_> {
+    /// Adds `tag` to the operations of this scope.
+    pub fn tag(&mut self, tag: &str) -> &mut Self {
+        self.tags.push(tag.to_owned());
+        self
+    }
+
+    /// Requires the security scheme `scheme_name` for the operations of this scope.
+    /// Calling it multiple times adds alternative requirements.
+    pub fn security(&mut self, scheme_name: &str, scopes: &[&str]) -> &mut Self {
+        let mut requirement = Map::default();
+        requirement.insert(
+            scheme_name.to_owned(),
+            scopes.iter().map(ToString::to_string).collect(),
+        );
+        self.security.get_or_insert_with(Vec::new).push(requirement);
+        self
+    }
+
+    /// Adds an error response with the resource's error type to the operations of this scope.
+    pub fn error(&mut self, status: u16, description: &str) -> &mut Self {
+        self.errors.push((status, description.to_owned()));
+        self
+    }
+
+    /// Registers the verbs of `R` at this scope, then calls `children` with the
+    /// scope of a single `R` document, e.g. `/api/organizers/{oid}`.
+    pub fn resource<R: Resource, F: FnOnce(&mut Scope<'_>)>(&mut self, children: F) -> &mut Self {
+        let base_path = ApiPath::new(self.prefix.clone(), self.ids.clone(), None);
+        self.builder.resource::<R>(&base_path);
+
+        let (collection_path, item_path) = Oas3Builder::resource_paths::<R>(&base_path);
+        for verb in R::verbs() {
+            let path = if verb.on_item() {
+                item_path.to_string()
+            } else {
+                collection_path.to_string()
+            };
+            self.decorate::<R::Error>(&path, &verb.method());
+        }
+
+        let mut ids = self.ids.clone();
+        ids.push(ApiId::new(&R::collection_name(), &R::key()));
+        let mut child = Scope {
+            builder: &mut *self.builder,
+            prefix: self.prefix.clone(),
+            ids,
+            tags: self.tags.clone(),
+            security: self.security.clone(),
+            errors: self.errors.clone(),
+        };
+        children(&mut child);
+        self
+    }
+
+    fn decorate<E: schemars::JsonSchema + serde::Serialize>(
+        &mut self,
+        path: &str,
+        method: &http::Method,
+    ) {
+        let mut error_responses = vec![];
+        for (status, description) in &self.errors {
+            let resp = self.builder.create_response::<E>(description.clone());
+            error_responses.push((status.to_string(), resp));
+        }
+        let op: &mut Operation = self
+            .builder
+            .generator
+            .operation_mut(path, method)
+            .expect("operation registered by Oas3Builder::resource");
+        for tag in &self.tags {
+            if !op.tags.contains(tag) {
+                op.tags.push(tag.clone());
+            }
+        }
+        if self.security.is_some() {
+            op.security.clone_from(&self.security);
+        }
+        for (status, resp) in error_responses {
+            op.responses
+                .responses
+                .entry(status)
+                .or_insert_with(|| resp.into());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Oas3Builder, Resource, Verb};
+    use schemars::JsonSchema;
+    use serde::Serialize;
+    use serde_json::json;
+
+    #[derive(Serialize, JsonSchema)]
+    pub struct TestDoc {
+        pub title: String,
+    }
+
+    macro_rules! test_resource {
+        ($name:ident, $document:expr, $key:expr) => {
+            struct $name;
+            impl Resource for $name {
+                type Form = TestDoc;
+                type Document = TestDoc;
+                type Collection = Vec<TestDoc>;
+                type Error = String;
+                fn document_name() -> String {
+                    $document.to_owned()
+                }
+                fn key() -> String {
+                    $key.to_owned()
+                }
+                fn verbs() -> Vec<Verb> {
+                    vec![Verb::List, Verb::Fetch]
+                }
+            }
+        };
+    }
+    test_resource!(Organizers, "Organizers", "{oid}");
+    test_resource!(Events, "Events", "{eid}");
+    test_resource!(Tickets, "Tickets", "{tid}");
+
+    #[test]
+    fn test_nested_scopes() {
+        let mut oasb = Oas3Builder::default();
+        oasb.scope("api")
+            .tag("Api")
+            .error(404, "Not Found")
+            .resource::<Organizers, _>(|organizers| {
+                organizers
+                    .security("bearerAuth", &["events"])
+                    .resource::<Events, _>(|events| {
+                        events.tag("Tickets").resource::<Tickets, _>(|_| {});
+                    });
+            });
+        let got = serde_json::to_value(oasb.build("1.0.0".to_owned())).unwrap();
+
+        let paths: Vec<&String> = got["paths"].as_object().unwrap().keys().collect();
+        assert_eq!(
+            vec![
+                "/api/organizers",
+                "/api/organizers/{oid}",
+                "/api/organizers/{oid}/events",
+                "/api/organizers/{oid}/events/{eid}",
+                "/api/organizers/{oid}/events/{eid}/tickets",
+                "/api/organizers/{oid}/events/{eid}/tickets/{tid}",
+            ],
+            paths
+        );
+
+        let organizers = &got["paths"]["/api/organizers"]["get"];
+        assert_eq!(json!(["Api"]), organizers["tags"]);
+        assert_eq!(json!(null), organizers["security"]);
+        assert_eq!(
+            json!("Not Found"),
+            organizers["responses"]["404"]["description"]
+        );
+
+        let ticket = &got["paths"]["/api/organizers/{oid}/events/{eid}/tickets/{tid}"]["get"];
+        assert_eq!(json!(["Api", "Tickets"]), ticket["tags"]);
+        assert_eq!(json!([{"bearerAuth": ["events"]}]), ticket["security"]);
+        assert_eq!(
+            json!("Not Found"),
+            ticket["responses"]["404"]["description"]
+        );
+        let params: Vec<&str> = ticket["parameters"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(vec!["oid", "eid", "tid"], params);
+    }
+}
@@ -0,0 +1,176 @@
+use contracts::requires;
+use heck::CamelCase;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use crate::apipath::ApiPath;
+use crate::generator::{Operation, OperationInfo, Parameter, RefOr, Responses};
+use crate::oasgen::Oas3Builder;
+use crate::resource::Verb;
+use crate::xtests::{tests_extensions, Test};
+
+impl Oas3Builder {
+    /// Registers a document that exists once per parent, e.g. `/api/me/profile`,
+    /// which has no collection and no key of its own.
+    /// `Fetch`, `Replace` and `Update` respond with `200` and `O`, `Delete` with `204`.
+    /// ```
+    /// # use oas_gen::{ApiPath, Oas3Builder, Verb};
+    /// # #[derive(serde::Serialize, schemars::JsonSchema)]
+    /// # pub struct Profile { name: String }
+    /// let mut oasb = Oas3Builder::default();
+    /// let profile_path = ApiPath::new(Some("api".to_owned()), vec![], Some("me/profile".to_owned()));
+    /// oasb.singleton::<Profile, Profile, String>(&profile_path, "Profile".to_owned(), None, &[Verb::Fetch, Verb::Replace]);
+    /// let spec = oasb.build("1.0.0".to_owned());
+    /// let profile = &spec.paths["/api/me/profile"];
+    /// assert_eq!(Some("fetchProfile".to_owned()), profile.get.as_ref().unwrap().operation_id);
+    /// assert_eq!(Some("replaceProfile".to_owned()), profile.put.as_ref().unwrap().operation_id);
+    /// ```
+    pub fn singleton<
+        I: JsonSchema + Serialize,
+        O: JsonSchema + Serialize,
+        E: JsonSchema + Serialize,
+    >(
+        &mut self,
+        web_path: &ApiPath,
+        document_name: String,
+        operation_description: Option<String>,
+        verbs: &[Verb],
+    ) {
+        self.singleton_with_tests::<I, O, E>(
+            web_path,
+            document_name,
+            operation_description,
+            verbs,
+            &[],
+        );
+    }
+
+    /// `tests` holds the tests of the operation of each verb.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if json serialization of `tests` fail
+    #[requires(!verbs.contains(&Verb::List), "a singleton has no collection to list")]
+    #[requires(!verbs.contains(&Verb::Create), "a singleton is created with Verb::Replace")]
+    pub fn singleton_with_tests<
+        I: JsonSchema + Serialize,
+        O: JsonSchema + Serialize,
+        E: JsonSchema + Serialize,
+    >(
+        &mut self,
+        web_path: &ApiPath,
+        document_name: String,
+        operation_description: Option<String>,
+        verbs: &[Verb],
+        tests: &[(Verb, Vec<Test>)],
+    ) {
+        for verb in verbs {
+            let (operation_name, status, has_body) = match verb {
+                Verb::Fetch => ("fetch", "200", false),
+                Verb::Replace => ("replace", "200", true),
+                Verb::Update => ("update", "200", true),
+                Verb::Delete => ("delete", "204", false),
+                Verb::List | Verb::Create => continue,
+            };
+            let operation_id = format!("{operation_name}{document_name}");
+            let summary = format!("{} {document_name}", operation_name.to_camel_case());
+
+            let mut resps = Responses::default();
+            let resp = if *verb == Verb::Delete {
+                self.create_response::<()>(document_name.clone())
+            } else {
+                self.create_response::<O>(document_name.clone())
+            };
+            resps.responses.insert(status.to_owned(), resp.into());
+            self.add_error_responses::<E>(&mut resps);
+
+            let request_body = if has_body {
+                self.create_request_body::<I>()
+            } else {
+                None
+            };
+
+            let mut parameters: Vec<RefOr<Parameter>> = vec![];
+            self.add_path_params(web_path.clone(), &mut parameters);
+
+            let verb_tests: Vec<Test> = tests
+                .iter()
+                .filter(|(tested, _)| tested == verb)
+                .flat_map(|(_, tests)| tests.iter().cloned())
+                .collect();
+            let extensions = tests_extensions(&web_path.to_string(), &verb_tests);
+
+            self.generator.add_operation(OperationInfo {
+                path: web_path.to_string(),
+                method: verb.method(),
+                operation: Operation {
+                    operation_id: Some(operation_id),
+                    summary: Some(summary),
+                    description: operation_description.clone(),
+                    responses: resps,
+                    request_body,
+                    parameters,
+                    extensions,
+                    ..Operation::default()
+                },
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::xtests::Test;
+    use crate::{ApiId, ApiPath, Oas3Builder, Verb};
+    use schemars::JsonSchema;
+    use serde::Serialize;
+    use serde_json::json;
+
+    #[derive(Serialize, JsonSchema)]
+    pub struct TestEvent {
+        pub title: String,
+    }
+
+    #[test]
+    fn test_singleton() {
+        let profile_path = ApiPath::new(
+            Some("api".to_owned()),
+            vec![ApiId::new("users", "{uid}")],
+            Some("profile".to_owned()),
+        );
+        let mut oasb = Oas3Builder::default();
+        oasb.singleton_with_tests::<TestEvent, TestEvent, String>(
+            &profile_path,
+            "Profile".to_owned(),
+            Some("The profile of a user.".to_owned()),
+            &[Verb::Fetch, Verb::Update, Verb::Delete],
+            &[(
+                Verb::Fetch,
+                vec![Test::new("Fetch a profile.").param("uid", "7").build()],
+            )],
+        );
+        let got = serde_json::to_value(oasb.build("1.0.0".to_owned())).unwrap();
+        let profile = &got["paths"]["/api/users/{uid}/profile"];
+
+        assert_eq!(json!("Fetch Profile"), profile["get"]["summary"]);
+        assert_eq!(
+            json!("The profile of a user."),
+            profile["get"]["description"]
+        );
+        assert_eq!(
+            json!([{"description": "Fetch a profile.", "params": {"uid": "7"}}]),
+            profile["get"]["x-tests"]
+        );
+        assert!(profile["patch"]["x-tests"].is_null());
+        assert!(profile["get"]["requestBody"].is_null());
+        assert!(profile["get"]["responses"]["200"].is_object());
+        assert_eq!(json!("updateProfile"), profile["patch"]["operationId"]);
+        assert!(profile["patch"]["requestBody"].is_object());
+        assert_eq!(
+            json!({"description": "Profile"}),
+            profile["delete"]["responses"]["204"]
+        );
+        assert_eq!(json!("uid"), profile["get"]["parameters"][0]["name"]);
+        assert!(profile["put"].is_null());
+    }
+}
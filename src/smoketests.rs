@@ -0,0 +1,280 @@
+use serde_json::Value;
+
+use crate::examples::media_example;
+use crate::generator::{ExampleValue, OpenApi, OpenApiGenerator, Operation, ParameterValue, RefOr};
+use crate::oasgen::Oas3Builder;
+use crate::xtests::{BodyAssertion, Test};
+
+impl Oas3Builder {
+    /// When enabled, `build` adds smoke tests to the `x-tests` of every operation
+    /// without tests: one sending the examples of the path and query parameters and
+    /// of the request body, expecting the lowest documented 2xx status, and negative
+    /// ones where the spec allows, see `smoke_tests_for`.
+    pub fn smoke_tests(&mut self, smoke_tests: bool) -> &mut Self {
+        self.smoke_tests = smoke_tests;
+        self
+    }
+}
+
+/// The smoke tests of `op`, none if a required parameter or request body has no example:
+/// - the success test, sending the examples and expecting the lowest documented 2xx status,
+/// - without auth, expecting 401, if the operation is secured and documents 401,
+/// - with an empty object as body, expecting 400, if the body schema has required
+///   properties and the operation documents 400.
+#[must_use]
+pub fn smoke_tests_for(openapi: &OpenApi, op: &Operation) -> Vec<Test> {
+    let Some(success) = op
+        .responses
+        .responses
+        .keys()
+        .filter(|status| status.starts_with('2'))
+        .filter_map(|status| status.parse::<u16>().ok())
+        .min()
+    else {
+        return vec![];
+    };
+    let name = op.operation_id.clone().unwrap_or_default();
+    let secured = op
+        .security
+        .as_ref()
+        .map_or(!openapi.security.is_empty(), |security| {
+            !security.is_empty()
+        });
+
+    let mut test = Test::new(&format!("Smoke test of {name}."));
+    if secured {
+        test = test.auth();
+    }
+    for parameter in &op.parameters {
+        let RefOr::Object(parameter) = parameter else {
+            continue;
+        };
+        let ParameterValue::Schema {
+            example, examples, ..
+        } = &parameter.value
+        else {
+            continue;
+        };
+        let example = example.as_ref().or_else(|| {
+            examples
+                .iter()
+                .flat_map(|examples| examples.values())
+                .find_map(|example| match &example.value {
+                    ExampleValue::Value(value) => Some(value),
+                    ExampleValue::ExternalValue(_) => None,
+                })
+        });
+        match (
+            example.and_then(to_param_value),
+            parameter.location.as_str(),
+        ) {
+            (Some(value), "path") => test = test.param(&parameter.name, &value),
+            (Some(value), "query") => test = test.query(&parameter.name, &value),
+            (Some(value), "header") => test = test.header(&parameter.name, &value),
+            (None, _) if parameter.required => return vec![],
+            _ => {}
+        }
+    }
+
+    let mut required_properties = false;
+    if let Some(RefOr::Object(request_body)) = &op.request_body {
+        let media = request_body.content.values().next();
+        match media.and_then(media_example) {
+            Some(example) => test = test.json_body(&example),
+            None if request_body.required => return vec![],
+            None => {}
+        }
+        required_properties = media
+            .and_then(|media| media.schema.as_ref())
+            .is_some_and(|schema| has_required_properties(openapi, schema));
+    }
+
+    let documented = |status: u16| op.responses.responses.contains_key(&status.to_string());
+    let has_content = matches!(
+        op.responses.responses.get(&success.to_string()),
+        Some(RefOr::Object(response)) if !response.content.is_empty()
+    );
+    let base = test.clone();
+    test = test.expect_status(success);
+    if has_content {
+        test = test.expect_body(BodyAssertion::MatchesSchema);
+    }
+    let mut tests = vec![test.build()];
+
+    if secured && documented(401) {
+        let mut unauthorized = base.clone().expect_status(401).build();
+        unauthorized.auth = false;
+        unauthorized.description = format!("{name} rejects requests without auth.");
+        tests.push(unauthorized);
+    }
+    if required_properties && documented(400) {
+        let mut bad_request = base
+            .json_body(&serde_json::json!({}))
+            .expect_status(400)
+            .build();
+        bad_request.description = format!("{name} rejects a body without its required properties.");
+        tests.push(bad_request);
+    }
+    tests
+}
+
+/// A parameter example as sent in a url: strings as is, arrays comma separated.
+fn to_param_value(example: &Value) -> Option<String> {
+    match example {
+        Value::String(text) => Some(text.clone()),
+        Value::Number(_) | Value::Bool(_) => Some(example.to_string()),
+        Value::Array(items) => items
+            .iter()
+            .map(|item| match item {
+                Value::Array(_) | Value::Object(_) => None,
+                _ => to_param_value(item),
+            })
+            .collect::<Option<Vec<_>>>()
+            .map(|items| items.join(",")),
+        Value::Null | Value::Object(_) => None,
+    }
+}
+
+fn has_required_properties(openapi: &OpenApi, schema: &schemars::schema::SchemaObject) -> bool {
+    let schema = match &schema.reference {
+        Some(reference) => {
+            let name = reference.rsplit('/').next().unwrap_or_default();
+            match openapi
+                .components
+                .as_ref()
+                .and_then(|components| components.schemas.get(name))
+            {
+                Some(schema) => schema,
+                None => return false,
+            }
+        }
+        None => schema,
+    };
+    schema
+        .object
+        .as_ref()
+        .is_some_and(|object| !object.required.is_empty())
+}
+
+/// Applies `Oas3Builder::smoke_tests` to the finished spec.
+pub(crate) fn add_smoke_tests(openapi: &mut OpenApi) {
+    let spec = openapi.clone();
+    for path_item in openapi.paths.values_mut() {
+        for (_, op) in OpenApiGenerator::path_item_operations(path_item) {
+            if op.extensions.contains_key("x-tests") {
+                continue;
+            }
+            let tests = smoke_tests_for(&spec, op);
+            if !tests.is_empty() {
+                let tests = serde_json::to_value(tests).expect("tests serialize to json");
+                op.extensions.insert("x-tests".to_owned(), tests);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::xtests::Test;
+    use crate::{ApiId, ApiPath, Oas3Builder};
+    use schemars::JsonSchema;
+    use serde::Serialize;
+    use serde_json::json;
+
+    #[derive(Serialize, JsonSchema)]
+    pub struct TestEvent {
+        pub title: String,
+    }
+
+    #[test]
+    fn test_smoke_tests() {
+        let event_path = ApiPath::new(
+            Some("api".to_owned()),
+            vec![ApiId::new("events", "{eid}")],
+            None,
+        );
+        let mut oasb = Oas3Builder::default();
+        oasb.smoke_tests(true);
+        oasb.fetch_with_tests::<TestEvent, String>(
+            &event_path,
+            "Events".to_owned(),
+            None,
+            &[Test::new("Hand written.").param("eid", "e1").build()],
+        );
+        oasb.replace::<TestEvent, TestEvent, String>(&event_path, "Events".to_owned(), None);
+        let mut requirement = crate::generator::Map::default();
+        requirement.insert("bearerAuth".to_owned(), vec![]);
+        let replace = oasb
+            .generator
+            .operation_mut("/api/events/{eid}", &http::Method::PUT)
+            .unwrap();
+        replace.security = Some(vec![requirement]);
+        oasb.request_example(
+            &event_path,
+            &http::Method::PUT,
+            &json!({"title": "Hackaton"}),
+        );
+        let got = serde_json::to_value(oasb.build("1.0.0".to_owned())).unwrap();
+        let _openapi_json: openapiv3::OpenAPI = serde_json::from_value(got.clone()).unwrap();
+
+        let item = &got["paths"]["/api/events/{eid}"];
+        assert_eq!(
+            json!([{"description": "Hand written.", "params": {"eid": "e1"}}]),
+            item["get"]["x-tests"]
+        );
+        assert_eq!(
+            json!([
+                {
+                    "description": "Smoke test of replaceEvents.",
+                    "auth": true,
+                    "params": {"eid": "8472"},
+                    "request": {"body": {"title": "Hackaton"}},
+                    "response": {"status": [201], "body": ["matchesSchema"]}
+                },
+                {
+                    "description": "replaceEvents rejects requests without auth.",
+                    "params": {"eid": "8472"},
+                    "request": {"body": {"title": "Hackaton"}},
+                    "response": {"status": [401]}
+                },
+                {
+                    "description": "replaceEvents rejects a body without its required properties.",
+                    "auth": true,
+                    "params": {"eid": "8472"},
+                    "request": {"body": {}},
+                    "response": {"status": [400]}
+                }
+            ]),
+            item["put"]["x-tests"]
+        );
+    }
+
+    #[test]
+    fn test_global_security() {
+        let event_path = ApiPath::new(
+            Some("api".to_owned()),
+            vec![ApiId::new("events", "{eid}")],
+            None,
+        );
+        let mut oasb = Oas3Builder::default();
+        oasb.smoke_tests(true);
+        oasb.replace::<TestEvent, TestEvent, String>(&event_path, "Events".to_owned(), None);
+        oasb.request_example(
+            &event_path,
+            &http::Method::PUT,
+            &json!({"title": "Hackaton"}),
+        );
+        let mut security_schemes = crate::generator::Map::default();
+        security_schemes.insert(
+            "bearerAuth".to_owned(),
+            crate::generator::RefOr::Object(Oas3Builder::create_bearer_scheme()),
+        );
+        let got =
+            serde_json::to_value(oasb.build_with_security("1.0.0".to_owned(), security_schemes))
+                .unwrap();
+
+        let tests = &got["paths"]["/api/events/{eid}"]["put"]["x-tests"];
+        assert_eq!(json!(true), tests[0]["auth"]);
+        assert_eq!(json!([401]), tests[1]["response"]["status"]);
+    }
+}
@@ -0,0 +1,555 @@
+use schemars::schema::{InstanceType, Schema, SchemaObject, SingleOrVec};
+use serde_json::{Map as JsonMap, Number, Value};
+
+use crate::generator::{Map, MediaType, OpenApi, OpenApiGenerator, RefOr};
+use crate::oasgen::Oas3Builder;
+use crate::validation::SchemaValidator;
+
+const WORDS: &[&str] = &[
+    "alpha", "harbor", "summit", "meadow", "orbit", "cedar", "lantern", "river", "copper", "maple",
+    "signal", "willow", "atlas", "ember", "falcon", "granite",
+];
+const CITIES: &[&str] = &["Budapest", "Lisbon", "Oslo", "Vienna", "Porto", "Krakow"];
+const COUNTRIES: &[&str] = &["HU", "PT", "NO", "AT", "PL", "DE"];
+
+/// Synthesises plausible values for schemas, e.g. to fill in missing examples.
+///
+/// Values respect the type, `format`, `enum`, `const`, numeric ranges, lengths
+/// and item counts of the schema, and strings are picked by property name
+/// heuristics, e.g. `email`, `createdAt` or `city`. The same seed yields the same
+/// values for the same schemas.
+/// ```
+/// # use oas_gen::ExampleGenerator;
+/// # use schemars::{gen::SchemaGenerator, JsonSchema};
+/// #[derive(JsonSchema)]
+/// struct Event { title: String, contact_email: String }
+///
+/// let mut gen = SchemaGenerator::default();
+/// let schema = gen.subschema_for::<Event>().into_object();
+/// let definitions = gen.definitions().iter()
+///     .map(|(name, schema)| (name.clone(), schema.clone().into_object()))
+///     .collect();
+/// let example = ExampleGenerator::new(&definitions, 7).generate(&schema);
+/// assert!(example["contact_email"].as_str().unwrap().contains('@'));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ExampleGenerator<'a> {
+    definitions: &'a Map<String, SchemaObject>,
+    rng: SplitMix64,
+    max_depth: usize,
+}
+
+impl<'a> ExampleGenerator<'a> {
+    /// A generator resolving `$ref`s by their last segment in `definitions`.
+    #[must_use]
+    pub fn new(definitions: &'a Map<String, SchemaObject>, seed: u64) -> Self {
+        ExampleGenerator {
+            definitions,
+            rng: SplitMix64(seed),
+            max_depth: 4,
+        }
+    }
+
+    /// Nesting depth below which optional properties and array items are left out,
+    /// which ends recursive schemas, defaults to 4.
+    #[must_use]
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// A value for `schema`.
+    pub fn generate(&mut self, schema: &SchemaObject) -> Value {
+        self.generate_object(schema, None, 0)
+    }
+
+    /// A value for every schema of `definitions`, by name.
+    pub fn generate_all(&mut self) -> Map<String, Value> {
+        let definitions = self.definitions;
+        definitions
+            .iter()
+            .map(|(name, schema)| (name.clone(), self.generate(schema)))
+            .collect()
+    }
+
+    fn generate_schema(&mut self, schema: &Schema, name: Option<&str>, depth: usize) -> Value {
+        match schema {
+            Schema::Bool(_) => Value::Null,
+            Schema::Object(schema) => self.generate_object(schema, name, depth),
+        }
+    }
+
+    fn generate_object(
+        &mut self,
+        schema: &SchemaObject,
+        name: Option<&str>,
+        depth: usize,
+    ) -> Value {
+        if let Some(reference) = &schema.reference {
+            let definitions = self.definitions;
+            return match reference
+                .rsplit('/')
+                .next()
+                .and_then(|n| definitions.get(n))
+            {
+                Some(definition) => self.generate_object(definition, name, depth + 1),
+                None => Value::Null,
+            };
+        }
+        if let Some(constant) = &schema.const_value {
+            return constant.clone();
+        }
+        if let Some(values) = schema.enum_values.as_ref().filter(|v| !v.is_empty()) {
+            return values[self.rng.below(values.len())].clone();
+        }
+        if let Some(subschemas) = &schema.subschemas {
+            if let Some(all_of) = &subschemas.all_of {
+                let mut merged = JsonMap::new();
+                for subschema in all_of {
+                    match self.generate_schema(subschema, name, depth) {
+                        Value::Object(object) => merged.extend(object),
+                        other if all_of.len() == 1 => return other,
+                        _ => {}
+                    }
+                }
+                if let Value::Object(own) = self.generate_properties(schema, depth) {
+                    merged.extend(own);
+                }
+                return Value::Object(merged);
+            }
+            let alternatives = subschemas.one_of.as_ref().or(subschemas.any_of.as_ref());
+            if let Some(alternatives) = alternatives.filter(|a| !a.is_empty()) {
+                let pick = self.rng.below(alternatives.len());
+                return self.generate_schema(&alternatives[pick], name, depth);
+            }
+        }
+
+        let instance_type = match &schema.instance_type {
+            Some(SingleOrVec::Single(it)) => Some(**it),
+            Some(SingleOrVec::Vec(its)) => its.iter().copied().find(|it| *it != InstanceType::Null),
+            None if schema.object.is_some() => Some(InstanceType::Object),
+            None if schema.array.is_some() => Some(InstanceType::Array),
+            None => None,
+        };
+        match instance_type {
+            Some(InstanceType::Object) => self.generate_properties(schema, depth),
+            Some(InstanceType::Array) => self.generate_items(schema, name, depth),
+            Some(InstanceType::String) => Value::String(self.generate_string(schema, name)),
+            Some(InstanceType::Integer) => self.generate_integer(schema),
+            Some(InstanceType::Number) => self.generate_number(schema),
+            Some(InstanceType::Boolean) => Value::Bool(self.rng.below(2) == 0),
+            Some(InstanceType::Null) | None => Value::Null,
+        }
+    }
+
+    fn generate_properties(&mut self, schema: &SchemaObject, depth: usize) -> Value {
+        let mut object = JsonMap::new();
+        if let Some(validation) = &schema.object {
+            for (name, property) in &validation.properties {
+                if validation.required.contains(name) || depth < self.max_depth {
+                    let value = self.generate_schema(property, Some(name), depth + 1);
+                    object.insert(name.clone(), value);
+                }
+            }
+            if let Some(additional) = &validation.additional_properties {
+                if validation.properties.is_empty() && depth < self.max_depth {
+                    let key = self.word().to_owned();
+                    let value = self.generate_schema(additional, None, depth + 1);
+                    object.insert(key, value);
+                }
+            }
+        }
+        Value::Object(object)
+    }
+
+    fn generate_items(&mut self, schema: &SchemaObject, name: Option<&str>, depth: usize) -> Value {
+        let Some(array) = &schema.array else {
+            return Value::Array(vec![]);
+        };
+        let min = array.min_items.unwrap_or(0) as usize;
+        let max = array.max_items.map_or(usize::MAX, |max| max as usize);
+        let preferred = if depth < self.max_depth {
+            1 + self.rng.below(2)
+        } else {
+            0
+        };
+        let count = preferred.clamp(min, max.max(min));
+        let mut items = Vec::with_capacity(count);
+        for i in 0..count {
+            let item = match &array.items {
+                Some(SingleOrVec::Single(item_schema)) => {
+                    self.generate_schema(item_schema, name, depth + 1)
+                }
+                Some(SingleOrVec::Vec(item_schemas)) => match item_schemas.get(i) {
+                    Some(item_schema) => self.generate_schema(item_schema, name, depth + 1),
+                    None => Value::Null,
+                },
+                None => Value::String(self.word().to_owned()),
+            };
+            items.push(item);
+        }
+        Value::Array(items)
+    }
+
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_possible_wrap,
+        clippy::cast_sign_loss
+    )]
+    fn generate_integer(&mut self, schema: &SchemaObject) -> Value {
+        let number = schema.number.as_deref();
+        let maximum = number.and_then(|n| n.maximum);
+        let mut min = number.and_then(|n| n.minimum).unwrap_or(match maximum {
+            Some(max) if max < 1.0 => max - 100.0,
+            _ => 1.0,
+        });
+        let mut max = maximum.unwrap_or(min.max(0.0) + 100.0);
+        if let Some(exclusive) = number.and_then(|n| n.exclusive_minimum) {
+            min = min.max(exclusive.floor() + 1.0);
+        }
+        if let Some(exclusive) = number.and_then(|n| n.exclusive_maximum) {
+            max = max.min(exclusive.ceil() - 1.0);
+        }
+        let (min, max) = (min.ceil() as i64, max.floor() as i64);
+        let span = max.saturating_sub(min).max(0) as u64;
+        let value = min.saturating_add((self.rng.next() % span.saturating_add(1)) as i64);
+        let value = match number.and_then(|n| n.multiple_of) {
+            Some(multiple_of) if multiple_of >= 1.0 => {
+                // the next multiple upwards, or the last one below `max`
+                let multiple_of = multiple_of as i64;
+                let up = value
+                    .saturating_add((multiple_of - value.rem_euclid(multiple_of)) % multiple_of);
+                if up <= max {
+                    up
+                } else {
+                    max - max.rem_euclid(multiple_of)
+                }
+            }
+            _ => value,
+        };
+        Value::Number(value.into())
+    }
+
+    fn generate_number(&mut self, schema: &SchemaObject) -> Value {
+        let number = schema.number.as_deref();
+        let min = number
+            .and_then(|n| n.minimum.or(n.exclusive_minimum))
+            .unwrap_or(0.0);
+        let max = number
+            .and_then(|n| n.maximum.or(n.exclusive_maximum))
+            .unwrap_or(min + 100.0);
+        let value = min + (max - min) * self.rng.unit();
+        let value = (value * 100.0).round() / 100.0;
+        let value = if value <= min || value >= max {
+            (min + max) / 2.0
+        } else {
+            value
+        };
+        Number::from_f64(value).map_or(Value::Null, Value::Number)
+    }
+
+    fn generate_string(&mut self, schema: &SchemaObject, name: Option<&str>) -> String {
+        let value = match schema.format.as_deref() {
+            Some("date-time") => self.date_time(),
+            Some("date") => self.date(),
+            Some("email") => self.email(),
+            Some("uuid") => self.uuid(),
+            Some("uri" | "url") => self.uri(),
+            Some("ipv4") => format!("192.0.2.{}", 1 + self.rng.below(254)),
+            Some("ipv6") => format!("2001:db8::{:x}", 1 + self.rng.below(0xfffe)),
+            _ => self.string_for_name(name.unwrap_or_default()),
+        };
+        let Some(string) = &schema.string else {
+            return value;
+        };
+        let mut value = value;
+        if let Some(min_length) = string.min_length {
+            while value.chars().count() < min_length as usize {
+                value.push('x');
+            }
+        }
+        if let Some(max_length) = string.max_length {
+            value = value.chars().take(max_length as usize).collect();
+        }
+        value
+    }
+
+    fn string_for_name(&mut self, name: &str) -> String {
+        let lower = name.to_lowercase().replace(['_', '-'], "");
+        if lower.ends_with("email") {
+            self.email()
+        } else if lower == "id"
+            || ["_id", "-id", "Id", "ID"].iter().any(|s| name.ends_with(s))
+            || lower.ends_with("uuid")
+        {
+            self.uuid()
+        } else if ["url", "uri", "link", "href"]
+            .iter()
+            .any(|s| lower.contains(s))
+        {
+            self.uri()
+        } else if name.ends_with("_at") || name.ends_with("At") || lower.ends_with("time") {
+            self.date_time()
+        } else if lower.contains("date") {
+            self.date()
+        } else if lower.contains("phone") {
+            format!("+36 1 555 {:04}", self.rng.below(10_000))
+        } else if lower.contains("city") {
+            CITIES[self.rng.below(CITIES.len())].to_owned()
+        } else if lower.contains("country") {
+            COUNTRIES[self.rng.below(COUNTRIES.len())].to_owned()
+        } else if lower.contains("name") || lower.contains("title") {
+            format!("{} {}", capitalize(self.word()), capitalize(self.word()))
+        } else if ["description", "note", "comment", "summary"]
+            .iter()
+            .any(|s| lower.contains(s))
+        {
+            format!(
+                "{} {} {}.",
+                capitalize(self.word()),
+                self.word(),
+                self.word()
+            )
+        } else {
+            self.word().to_owned()
+        }
+    }
+
+    fn word(&mut self) -> &'static str {
+        WORDS[self.rng.below(WORDS.len())]
+    }
+
+    fn date(&mut self) -> String {
+        format!(
+            "20{:02}-{:02}-{:02}",
+            20 + self.rng.below(10),
+            1 + self.rng.below(12),
+            1 + self.rng.below(28)
+        )
+    }
+
+    fn date_time(&mut self) -> String {
+        let date = self.date();
+        format!(
+            "{date}T{:02}:{:02}:00Z",
+            self.rng.below(24),
+            self.rng.below(4) * 15
+        )
+    }
+
+    fn email(&mut self) -> String {
+        format!("{}.{}@example.com", self.word(), self.word())
+    }
+
+    fn uri(&mut self) -> String {
+        format!("https://example.com/{}", self.word())
+    }
+
+    fn uuid(&mut self) -> String {
+        let hex = format!("{:016x}{:016x}", self.rng.next(), self.rng.next());
+        format!(
+            "{}-{}-4{}-a{}-{}",
+            &hex[0..8],
+            &hex[8..12],
+            &hex[13..16],
+            &hex[17..20],
+            &hex[20..32]
+        )
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars.next().map_or_else(String::new, |first| {
+        first.to_uppercase().chain(chars).collect()
+    })
+}
+
+/// The `SplitMix64` generator, small and stable across versions so seeded output
+/// stays reproducible.
+#[derive(Debug, Clone)]
+struct SplitMix64(u64);
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `0..n`.
+    #[allow(clippy::cast_possible_truncation)]
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    /// A number in `0.0..1.0`.
+    #[allow(clippy::cast_precision_loss)]
+    fn unit(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+impl Oas3Builder {
+    /// Makes `build` synthesise an example, seeded with `seed`, for every request
+    /// body and response without one. Synthesised values not matching their schema,
+    /// e.g. because of a `pattern`, are left out.
+    pub fn synthesize_examples(&mut self, seed: u64) -> &mut Self {
+        self.example_seed = Some(seed);
+        self
+    }
+}
+
+/// Applies `Oas3Builder::synthesize_examples` to the finished spec.
+pub(crate) fn synthesize_examples(openapi: &mut OpenApi, seed: u64) {
+    let definitions = openapi
+        .components
+        .as_ref()
+        .map(|components| components.schemas.clone())
+        .unwrap_or_default();
+    let mut generator = ExampleGenerator::new(&definitions, seed);
+    let validator = SchemaValidator::new(&definitions);
+    let mut fill = |media: &mut MediaType| {
+        if media.example.is_some() || media.examples.is_some() {
+            return;
+        }
+        if let Some(schema) = &media.schema {
+            let example = generator.generate(schema);
+            if validator.validate(&example, schema).is_ok() {
+                media.example = Some(example);
+            }
+        }
+    };
+    for path_item in openapi.paths.values_mut() {
+        for (_, op) in OpenApiGenerator::path_item_operations(path_item) {
+            if let Some(RefOr::Object(request_body)) = &mut op.request_body {
+                request_body.content.values_mut().for_each(&mut fill);
+            }
+            for response in op.responses.responses.values_mut() {
+                if let RefOr::Object(response) = response {
+                    response.content.values_mut().for_each(&mut fill);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ExampleGenerator;
+    use crate::generator::Map;
+    use crate::{ApiId, ApiPath, Oas3Builder, SchemaValidator};
+    use schemars::gen::SchemaGenerator;
+    use schemars::JsonSchema;
+    use serde::Serialize;
+    use serde_json::json;
+
+    #[derive(Serialize, JsonSchema)]
+    #[serde(rename_all = "camelCase")]
+    pub struct TestOrganizer {
+        pub id: String,
+        pub contact_email: String,
+        pub city: String,
+        pub created_at: String,
+        #[schemars(range(min = 10, max = 20))]
+        pub seats: u32,
+        #[schemars(length(min = 3, max = 3))]
+        pub code: String,
+        pub status: TestStatus,
+        pub parent: Option<Box<TestOrganizer>>,
+        pub tags: Vec<String>,
+    }
+
+    #[derive(Serialize, JsonSchema)]
+    #[allow(dead_code)]
+    pub enum TestStatus {
+        Active,
+        Suspended,
+    }
+
+    #[test]
+    fn test_generate() {
+        let mut gen = SchemaGenerator::new(Oas3Builder::default_settings());
+        let schema = gen.subschema_for::<TestOrganizer>().into_object();
+        let definitions: Map<_, _> = gen
+            .definitions()
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone().into_object()))
+            .collect();
+
+        let example = ExampleGenerator::new(&definitions, 42).generate(&schema);
+        assert_eq!(
+            Ok(()),
+            SchemaValidator::new(&definitions).validate(&example, &schema)
+        );
+        assert_eq!(36, example["id"].as_str().unwrap().len());
+        assert!(example["contactEmail"]
+            .as_str()
+            .unwrap()
+            .ends_with("@example.com"));
+        assert!(example["createdAt"].as_str().unwrap().ends_with('Z'));
+        let seats = example["seats"].as_u64().unwrap();
+        assert!((10..=20).contains(&seats));
+        assert_eq!(3, example["code"].as_str().unwrap().len());
+
+        // reproducible
+        let again = ExampleGenerator::new(&definitions, 42).generate(&schema);
+        assert_eq!(example, again);
+        let other = ExampleGenerator::new(&definitions, 43).generate(&schema);
+        assert_ne!(example, other);
+    }
+
+    #[test]
+    fn test_generate_integer_bounds() {
+        let definitions = Map::new();
+        let mut gen = ExampleGenerator::new(&definitions, 3);
+        for seed in 0..20 {
+            gen.rng = super::SplitMix64(seed);
+            let below: schemars::schema::SchemaObject =
+                serde_json::from_value(json!({"type": "integer", "maximum": -5})).unwrap();
+            assert!(gen.generate(&below).as_i64().unwrap() <= -5);
+
+            let multiple: schemars::schema::SchemaObject = serde_json::from_value(
+                json!({"type": "integer", "minimum": 5, "maximum": 10, "multipleOf": 4}),
+            )
+            .unwrap();
+            assert_eq!(json!(8), gen.generate(&multiple));
+        }
+    }
+
+    #[test]
+    fn test_id_names() {
+        let definitions = Map::new();
+        let mut gen = ExampleGenerator::new(&definitions, 3);
+        for name in ["id", "event_id", "eventId", "organizerID"] {
+            assert_eq!(36, gen.string_for_name(name).len(), "{name}");
+        }
+        for name in ["paid", "valid", "void"] {
+            assert_ne!(36, gen.string_for_name(name).len(), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_synthesize_examples() {
+        let path = ApiPath::new(
+            Some("api".to_owned()),
+            vec![ApiId::new("organizers", "{oid}")],
+            None,
+        );
+        let mut oasb = Oas3Builder::default();
+        oasb.replace::<TestOrganizer, TestOrganizer, String>(&path, "Organizers".to_owned(), None);
+        oasb.response_example(&path, &http::Method::PUT, 400, &"seats out of range");
+        oasb.synthesize_examples(1);
+        let got = serde_json::to_value(oasb.build("1.0.0".to_owned())).unwrap();
+
+        let json = "application/json; charset=utf-8";
+        let put = &got["paths"]["/api/organizers/{oid}"]["put"];
+        assert!(put["requestBody"]["content"][json]["example"]["contactEmail"].is_string());
+        assert!(put["responses"]["201"]["content"][json]["example"]["city"].is_string());
+        assert_eq!(
+            json!("seats out of range"),
+            put["responses"]["400"]["content"][json]["example"]
+        );
+    }
+}
@@ -0,0 +1,251 @@
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use schemars::schema::SchemaObject;
+use schemars::visit::{visit_schema_object, Visitor};
+use serde_json::Value;
+
+use crate::generator::{OpenApi, OpenApiGenerator, Operation, Parameter, RefOr};
+use crate::oasgen::Oas3Builder;
+
+/// A transformation of the finished spec, registered with `Oas3Builder::transform`.
+///
+/// The default `transform` calls `transform_operation` and `transform_parameter` for
+/// every operation and its inline parameters, then `transform_schema` for every
+/// component schema; implement the hooks needed, or `transform` itself.
+pub trait SpecTransform: Debug {
+    fn transform(&self, openapi: &mut OpenApi) {
+        for (path, path_item) in &mut openapi.paths {
+            for (method, op) in OpenApiGenerator::path_item_operations(path_item) {
+                self.transform_operation(path, &method, op);
+                for parameter in &mut op.parameters {
+                    if let RefOr::Object(parameter) = parameter {
+                        self.transform_parameter(parameter);
+                    }
+                }
+            }
+        }
+        if let Some(components) = &mut openapi.components {
+            for (name, schema) in &mut components.schemas {
+                self.transform_schema(name, schema);
+            }
+        }
+    }
+
+    fn transform_operation(&self, _path: &str, _method: &http::Method, _op: &mut Operation) {}
+
+    fn transform_parameter(&self, _parameter: &mut Parameter) {}
+
+    fn transform_schema(&self, _name: &str, _schema: &mut SchemaObject) {}
+}
+
+impl Oas3Builder {
+    /// Registers a transformation `build` and `build_with_security` run over the
+    /// finished spec, security schemes included, in registration order. They run
+    /// before `dedup_components`, so they see every inline parameter and response.
+    pub fn transform<T: SpecTransform + 'static>(&mut self, transform: T) -> &mut Self {
+        self.transforms.push(Arc::new(transform));
+        self
+    }
+}
+
+/// Runs a function over the finished spec.
+#[derive(Debug, Clone, Copy)]
+pub struct FnTransform(pub fn(&mut OpenApi));
+impl SpecTransform for FnTransform {
+    fn transform(&self, openapi: &mut OpenApi) {
+        (self.0)(openapi);
+    }
+}
+
+/// Removes the properties named `names` from every component and operation schema,
+/// e.g. internal fields.
+#[derive(Debug, Clone)]
+pub struct StripProperties(pub Vec<String>);
+impl SpecTransform for StripProperties {
+    fn transform_operation(&self, _path: &str, _method: &http::Method, op: &mut Operation) {
+        OpenApiGenerator::visit_operation(&mut StripPropertiesVisitor(&self.0), op);
+    }
+
+    fn transform_schema(&self, _name: &str, schema: &mut SchemaObject) {
+        StripPropertiesVisitor(&self.0).visit_schema_object(schema);
+    }
+}
+
+struct StripPropertiesVisitor<'a>(&'a [String]);
+impl Visitor for StripPropertiesVisitor<'_> {
+    fn visit_schema_object(&mut self, schema: &mut SchemaObject) {
+        if let Some(object) = &mut schema.object {
+            for name in self.0 {
+                object.properties.remove(name);
+                object.required.remove(name);
+            }
+        }
+        visit_schema_object(self, schema);
+    }
+}
+
+/// Adds the extension `name`, e.g. `x-internal`, with `value` to every operation.
+#[derive(Debug, Clone)]
+pub struct OperationExtension {
+    pub name: String,
+    pub value: Value,
+}
+impl SpecTransform for OperationExtension {
+    fn transform_operation(&self, _path: &str, _method: &http::Method, op: &mut Operation) {
+        op.extensions.insert(self.name.clone(), self.value.clone());
+    }
+}
+
+/// Rewrites the summaries and descriptions of operations, parameters and schemas.
+#[derive(Debug, Clone, Copy)]
+pub struct RewriteDescriptions(pub fn(&str) -> String);
+impl RewriteDescriptions {
+    fn rewrite(self, text: &mut Option<String>) {
+        if let Some(text) = text {
+            *text = (self.0)(text);
+        }
+    }
+}
+impl SpecTransform for RewriteDescriptions {
+    fn transform_operation(&self, _path: &str, _method: &http::Method, op: &mut Operation) {
+        self.rewrite(&mut op.summary);
+        self.rewrite(&mut op.description);
+        for response in op.responses.responses.values_mut() {
+            if let RefOr::Object(response) = response {
+                response.description = (self.0)(&response.description);
+            }
+        }
+    }
+
+    fn transform_parameter(&self, parameter: &mut Parameter) {
+        self.rewrite(&mut parameter.description);
+    }
+
+    fn transform_schema(&self, _name: &str, schema: &mut SchemaObject) {
+        let mut visitor = *self;
+        visitor.visit_schema_object(schema);
+    }
+}
+impl Visitor for RewriteDescriptions {
+    fn visit_schema_object(&mut self, schema: &mut SchemaObject) {
+        if let Some(metadata) = &mut schema.metadata {
+            self.rewrite(&mut metadata.description);
+        }
+        visit_schema_object(self, schema);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FnTransform, OperationExtension, RewriteDescriptions, StripProperties};
+    use crate::{ApiId, ApiPath, Oas3Builder};
+    use schemars::JsonSchema;
+    use serde::Serialize;
+    use serde_json::json;
+
+    #[derive(Serialize, JsonSchema)]
+    pub struct TestVenue {
+        pub name: String,
+        pub internal_id: u64,
+    }
+
+    /// The event.
+    #[derive(Serialize, JsonSchema)]
+    pub struct TestEvent {
+        pub title: String,
+        pub internal_id: u64,
+        pub venue: TestVenue,
+    }
+
+    #[test]
+    fn test_transforms() {
+        let event_path = ApiPath::new(
+            Some("api".to_owned()),
+            vec![ApiId::new("events", "{eid}")],
+            None,
+        );
+        let mut oasb = Oas3Builder::default();
+        oasb.fetch::<TestEvent, String>(&event_path, "Events".to_owned(), None);
+        oasb.transform(StripProperties(vec!["internal_id".to_owned()]))
+            .transform(OperationExtension {
+                name: "x-audience".to_owned(),
+                value: json!("public"),
+            })
+            .transform(RewriteDescriptions(|text| {
+                text.replace("event", "happening")
+            }))
+            .transform(FnTransform(|openapi| {
+                openapi.info.title = "Events".to_owned();
+                let schemes = &openapi.components.as_ref().unwrap().security_schemes;
+                assert!(schemes.contains_key("bearerAuth"));
+            }));
+        let mut security_schemes = crate::generator::Map::default();
+        security_schemes.insert(
+            "bearerAuth".to_owned(),
+            crate::generator::RefOr::Object(Oas3Builder::create_bearer_scheme()),
+        );
+        let got =
+            serde_json::to_value(oasb.build_with_security("1.0.0".to_owned(), security_schemes))
+                .unwrap();
+        let _openapi_json: openapiv3::OpenAPI = serde_json::from_value(got.clone()).unwrap();
+
+        assert_eq!(json!("Events"), got["info"]["title"]);
+        let schemas = &got["components"]["schemas"];
+        assert_eq!(json!(["title", "venue"]), schemas["TestEvent"]["required"]);
+        assert_eq!(json!(["name"]), schemas["TestVenue"]["required"]);
+        assert_eq!(json!("The happening."), schemas["TestEvent"]["description"]);
+
+        let fetch = &got["paths"]["/api/events/{eid}"]["get"];
+        assert_eq!(json!("public"), fetch["x-audience"]);
+    }
+
+    #[test]
+    fn test_transform_before_dedup() {
+        let event_path = ApiPath::new(
+            Some("api".to_owned()),
+            vec![ApiId::new("events", "{eid}")],
+            None,
+        );
+        let mut oasb = Oas3Builder::default();
+        oasb.fetch::<TestEvent, String>(&event_path, "Events".to_owned(), None);
+        oasb.replace::<TestEvent, TestEvent, String>(&event_path, "Events".to_owned(), None);
+        oasb.dedup_components(true)
+            .transform(RewriteDescriptions(str::to_uppercase));
+        let got = serde_json::to_value(oasb.build("1.0.0".to_owned())).unwrap();
+
+        let components = &got["components"];
+        assert_eq!(
+            json!({"$ref": "#/components/parameters/eid"}),
+            got["paths"]["/api/events/{eid}"]["get"]["parameters"][0]
+        );
+        let description = components["parameters"]["eid"]["description"]
+            .as_str()
+            .unwrap();
+        assert_eq!(description.to_uppercase(), description);
+        assert_eq!(
+            json!("INTERNAL SERVER ERROR"),
+            components["responses"]["InternalServerError"]["description"]
+        );
+    }
+
+    #[test]
+    fn test_strip_inline_properties() {
+        let event_path = ApiPath::new(
+            Some("api".to_owned()),
+            vec![ApiId::new("events", "{eid}")],
+            None,
+        );
+        let mut settings = Oas3Builder::default_settings();
+        settings.inline_subschemas = true;
+        let mut oasb = Oas3Builder::with_settings(settings);
+        oasb.replace::<TestEvent, (), String>(&event_path, "Events".to_owned(), None);
+        oasb.transform(StripProperties(vec!["internal_id".to_owned()]));
+        let got = serde_json::to_value(oasb.build("1.0.0".to_owned())).unwrap();
+
+        let event = &got["paths"]["/api/events/{eid}"]["put"]["requestBody"]["content"]
+            ["application/json; charset=utf-8"]["schema"];
+        assert_eq!(json!(["title", "venue"]), event["required"]);
+        assert_eq!(json!(["name"]), event["properties"]["venue"]["required"]);
+    }
+}
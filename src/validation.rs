@@ -0,0 +1,617 @@
+use schemars::schema::{InstanceType, Schema, SchemaObject, SingleOrVec};
+use serde_json::Value;
+use std::sync::OnceLock;
+
+use crate::constraints::compiled_regex;
+use crate::generator::{Map, OpenApi};
+
+/// A value not matching its schema.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationError {
+    /// The JSON pointer of the offending value, e.g. `/events/0/title`, empty for the root.
+    pub pointer: String,
+    /// The schema keyword the value violates, e.g. `required` or `format`.
+    pub keyword: &'static str,
+    pub message: String,
+}
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let pointer = if self.pointer.is_empty() {
+            "/"
+        } else {
+            &self.pointer
+        };
+        write!(f, "{pointer}: {}", self.message)
+    }
+}
+impl std::error::Error for ValidationError {}
+
+/// Validates values against the `OpenAPI` 3.0 flavoured schemas of a spec: `$ref`s
+/// are resolved into `components.schemas`, `nullable` is honoured and the
+/// `date-time`, `date`, `email`, `uuid`, `uri`, `ipv4`, `ipv6` and schemars integer
+/// formats are checked, other formats are ignored.
+///
+/// Patterns the `regex` crate can not compile are ignored like unknown formats.
+/// ```
+/// # use oas_gen::SchemaValidator;
+/// # use schemars::{gen::SchemaGenerator, JsonSchema};
+/// #[derive(JsonSchema)]
+/// struct Event { title: String }
+///
+/// let mut gen = SchemaGenerator::default();
+/// let schema = gen.subschema_for::<Event>().into_object();
+/// let definitions = gen.definitions().iter()
+///     .map(|(name, schema)| (name.clone(), schema.clone().into_object()))
+///     .collect();
+/// let validator = SchemaValidator::new(&definitions);
+/// let errors = validator.validate(&serde_json::json!({"title": 3}), &schema).unwrap_err();
+/// assert_eq!("/title", errors[0].pointer);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SchemaValidator<'a> {
+    definitions: &'a Map<String, SchemaObject>,
+}
+
+impl<'a> SchemaValidator<'a> {
+    /// A validator resolving `$ref`s by their last segment in `definitions`.
+    #[must_use]
+    pub fn new(definitions: &'a Map<String, SchemaObject>) -> Self {
+        SchemaValidator { definitions }
+    }
+
+    /// A validator resolving `$ref`s into the `components.schemas` of `openapi`,
+    /// a spec without components has no definitions.
+    #[must_use]
+    pub fn for_spec(openapi: &'a OpenApi) -> Self {
+        static EMPTY: OnceLock<Map<String, SchemaObject>> = OnceLock::new();
+        let definitions = match &openapi.components {
+            Some(components) => &components.schemas,
+            None => EMPTY.get_or_init(Map::new),
+        };
+        SchemaValidator::new(definitions)
+    }
+
+    /// Validates `value` against `schema`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` with every violation found, in document order.
+    pub fn validate(
+        &self,
+        value: &Value,
+        schema: &SchemaObject,
+    ) -> Result<(), Vec<ValidationError>> {
+        let mut errors = vec![];
+        self.validate_object(value, schema, "", &mut errors);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    fn is_valid(&self, value: &Value, schema: &Schema) -> bool {
+        let mut errors = vec![];
+        self.validate_schema(value, schema, "", &mut errors);
+        errors.is_empty()
+    }
+
+    fn validate_schema(
+        &self,
+        value: &Value,
+        schema: &Schema,
+        pointer: &str,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        match schema {
+            Schema::Bool(true) => {}
+            Schema::Bool(false) => push(errors, pointer, "false", "no value is allowed".to_owned()),
+            Schema::Object(schema) => self.validate_object(value, schema, pointer, errors),
+        }
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn validate_object(
+        &self,
+        value: &Value,
+        schema: &SchemaObject,
+        pointer: &str,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        if let Some(reference) = &schema.reference {
+            match reference
+                .rsplit('/')
+                .next()
+                .and_then(|name| self.definitions.get(name))
+            {
+                Some(definition) => self.validate_object(value, definition, pointer, errors),
+                None => push(
+                    errors,
+                    pointer,
+                    "$ref",
+                    format!("unknown reference {reference}"),
+                ),
+            }
+            return;
+        }
+        if value.is_null() && schema.extensions.get("nullable") == Some(&Value::Bool(true)) {
+            return;
+        }
+        if let Some(instance_type) = &schema.instance_type {
+            let types: &[InstanceType] = match instance_type {
+                SingleOrVec::Single(it) => std::slice::from_ref(&**it),
+                SingleOrVec::Vec(its) => its,
+            };
+            if !types.iter().any(|it| has_type(value, *it)) {
+                push(
+                    errors,
+                    pointer,
+                    "type",
+                    format!("expected {types:?}, found {value}"),
+                );
+                return;
+            }
+        }
+        if let Some(values) = &schema.enum_values {
+            if !values.contains(value) {
+                push(
+                    errors,
+                    pointer,
+                    "enum",
+                    format!("{value} is not one of {values:?}"),
+                );
+            }
+        }
+        if let Some(constant) = &schema.const_value {
+            if constant != value {
+                push(
+                    errors,
+                    pointer,
+                    "const",
+                    format!("expected {constant}, found {value}"),
+                );
+            }
+        }
+        if let Some(format) = &schema.format {
+            if let Some(reason) = check_format(value, format) {
+                push(errors, pointer, "format", reason);
+            }
+        }
+
+        if let (Some(number), Some(n)) = (&schema.number, value.as_f64()) {
+            if let Some(minimum) = number.minimum {
+                if n < minimum {
+                    push(
+                        errors,
+                        pointer,
+                        "minimum",
+                        format!("{n} is less than {minimum}"),
+                    );
+                }
+            }
+            if let Some(maximum) = number.maximum {
+                if n > maximum {
+                    push(
+                        errors,
+                        pointer,
+                        "maximum",
+                        format!("{n} is greater than {maximum}"),
+                    );
+                }
+            }
+            if let Some(minimum) = number.exclusive_minimum {
+                if n <= minimum {
+                    push(
+                        errors,
+                        pointer,
+                        "exclusiveMinimum",
+                        format!("{n} is not greater than {minimum}"),
+                    );
+                }
+            }
+            if let Some(maximum) = number.exclusive_maximum {
+                if n >= maximum {
+                    push(
+                        errors,
+                        pointer,
+                        "exclusiveMaximum",
+                        format!("{n} is not less than {maximum}"),
+                    );
+                }
+            }
+            if let Some(multiple_of) = number.multiple_of {
+                // a tolerance, as 0.3 / 0.1 is not exactly 3 in floating point
+                let quotient = n / multiple_of;
+                if (quotient - quotient.round()).abs() > 1e-9 * quotient.abs().max(1.0) {
+                    push(
+                        errors,
+                        pointer,
+                        "multipleOf",
+                        format!("{n} is not a multiple of {multiple_of}"),
+                    );
+                }
+            }
+        }
+
+        if let (Some(string), Value::String(s)) = (&schema.string, value) {
+            let length = s.chars().count();
+            if let Some(min_length) = string.min_length {
+                if length < min_length as usize {
+                    push(
+                        errors,
+                        pointer,
+                        "minLength",
+                        format!("is shorter than {min_length} characters"),
+                    );
+                }
+            }
+            if let Some(max_length) = string.max_length {
+                if length > max_length as usize {
+                    push(
+                        errors,
+                        pointer,
+                        "maxLength",
+                        format!("is longer than {max_length} characters"),
+                    );
+                }
+            }
+            if let Some(pattern) = &string.pattern {
+                if compiled_regex(pattern).is_ok_and(|re| !re.is_match(s)) {
+                    push(
+                        errors,
+                        pointer,
+                        "pattern",
+                        format!("does not match `{pattern}`"),
+                    );
+                }
+            }
+        }
+
+        if let Some(subschemas) = &schema.subschemas {
+            for subschema in subschemas.all_of.iter().flatten() {
+                self.validate_schema(value, subschema, pointer, errors);
+            }
+            if let Some(any_of) = &subschemas.any_of {
+                if !any_of.iter().any(|s| self.is_valid(value, s)) {
+                    push(
+                        errors,
+                        pointer,
+                        "anyOf",
+                        "matches none of the anyOf schemas".to_owned(),
+                    );
+                }
+            }
+            if let Some(one_of) = &subschemas.one_of {
+                let matches = one_of.iter().filter(|s| self.is_valid(value, s)).count();
+                if matches != 1 {
+                    push(
+                        errors,
+                        pointer,
+                        "oneOf",
+                        format!("matches {matches} of the oneOf schemas instead of 1"),
+                    );
+                }
+            }
+            if let Some(not) = &subschemas.not {
+                if self.is_valid(value, not) {
+                    push(errors, pointer, "not", "matches the not schema".to_owned());
+                }
+            }
+        }
+
+        if let (Some(object), Value::Object(map)) = (&schema.object, value) {
+            for name in &object.required {
+                if !map.contains_key(name) {
+                    push(
+                        errors,
+                        pointer,
+                        "required",
+                        format!("missing required property `{name}`"),
+                    );
+                }
+            }
+            if let Some(min_properties) = object.min_properties {
+                if map.len() < min_properties as usize {
+                    push(
+                        errors,
+                        pointer,
+                        "minProperties",
+                        format!("has less than {min_properties} properties"),
+                    );
+                }
+            }
+            if let Some(max_properties) = object.max_properties {
+                if map.len() > max_properties as usize {
+                    push(
+                        errors,
+                        pointer,
+                        "maxProperties",
+                        format!("has more than {max_properties} properties"),
+                    );
+                }
+            }
+            for (name, property) in map {
+                let property_pointer = format!("{pointer}/{}", escape(name));
+                let mut matched = false;
+                if let Some(property_schema) = object.properties.get(name) {
+                    matched = true;
+                    self.validate_schema(property, property_schema, &property_pointer, errors);
+                }
+                for (pattern, pattern_schema) in &object.pattern_properties {
+                    if compiled_regex(pattern).is_ok_and(|re| re.is_match(name)) {
+                        matched = true;
+                        self.validate_schema(property, pattern_schema, &property_pointer, errors);
+                    }
+                }
+                if !matched {
+                    if let Some(additional) = &object.additional_properties {
+                        self.validate_schema(property, additional, &property_pointer, errors);
+                    }
+                }
+                if let Some(property_names) = &object.property_names {
+                    let name = Value::String(name.clone());
+                    self.validate_schema(&name, property_names, &property_pointer, errors);
+                }
+            }
+        }
+
+        if let (Some(array), Value::Array(items)) = (&schema.array, value) {
+            if let Some(min_items) = array.min_items {
+                if items.len() < min_items as usize {
+                    push(
+                        errors,
+                        pointer,
+                        "minItems",
+                        format!("has less than {min_items} items"),
+                    );
+                }
+            }
+            if let Some(max_items) = array.max_items {
+                if items.len() > max_items as usize {
+                    push(
+                        errors,
+                        pointer,
+                        "maxItems",
+                        format!("has more than {max_items} items"),
+                    );
+                }
+            }
+            if array.unique_items == Some(true) {
+                let duplicate = items
+                    .iter()
+                    .enumerate()
+                    .any(|(i, item)| items[..i].contains(item));
+                if duplicate {
+                    push(
+                        errors,
+                        pointer,
+                        "uniqueItems",
+                        "has duplicate items".to_owned(),
+                    );
+                }
+            }
+            if let Some(contains) = &array.contains {
+                if !items.iter().any(|item| self.is_valid(item, contains)) {
+                    push(
+                        errors,
+                        pointer,
+                        "contains",
+                        "has no item matching the contains schema".to_owned(),
+                    );
+                }
+            }
+            for (i, item) in items.iter().enumerate() {
+                let item_pointer = format!("{pointer}/{i}");
+                let item_schema = match &array.items {
+                    Some(SingleOrVec::Single(item_schema)) => Some(&**item_schema),
+                    Some(SingleOrVec::Vec(item_schemas)) => {
+                        item_schemas.get(i).or(array.additional_items.as_deref())
+                    }
+                    None => None,
+                };
+                if let Some(item_schema) = item_schema {
+                    self.validate_schema(item, item_schema, &item_pointer, errors);
+                }
+            }
+        }
+    }
+}
+
+fn push(errors: &mut Vec<ValidationError>, pointer: &str, keyword: &'static str, message: String) {
+    errors.push(ValidationError {
+        pointer: pointer.to_owned(),
+        keyword,
+        message,
+    });
+}
+
+fn has_type(value: &Value, instance_type: InstanceType) -> bool {
+    match instance_type {
+        InstanceType::Null => value.is_null(),
+        InstanceType::Boolean => value.is_boolean(),
+        InstanceType::Object => value.is_object(),
+        InstanceType::Array => value.is_array(),
+        InstanceType::Number => value.is_number(),
+        InstanceType::String => value.is_string(),
+        InstanceType::Integer => {
+            value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|f| f.fract() == 0.0)
+        }
+    }
+}
+
+/// The reason `value` does not match `format`, `None` if it does or the format is unknown.
+fn check_format(value: &Value, format: &str) -> Option<String> {
+    if let Some(n) = value.as_f64() {
+        #[allow(clippy::cast_precision_loss)]
+        let (min, max) = match format {
+            "int8" => (f64::from(i8::MIN), f64::from(i8::MAX)),
+            "int16" => (f64::from(i16::MIN), f64::from(i16::MAX)),
+            "int32" => (f64::from(i32::MIN), f64::from(i32::MAX)),
+            "int64" => (i64::MIN as f64, i64::MAX as f64),
+            "uint8" => (0.0, f64::from(u8::MAX)),
+            "uint16" => (0.0, f64::from(u16::MAX)),
+            "uint32" => (0.0, f64::from(u32::MAX)),
+            "uint64" | "uint" => (0.0, u64::MAX as f64),
+            _ => return None,
+        };
+        return if n < min || n > max {
+            Some(format!("{value} is out of the {format} range"))
+        } else {
+            None
+        };
+    }
+    let s = value.as_str()?;
+    let valid = match format {
+        "date-time" => regex_match(
+            r"^\d{4}-\d{2}-\d{2}[Tt]\d{2}:\d{2}:\d{2}(\.\d+)?([Zz]|[+-]\d{2}:\d{2})$",
+            s,
+        ),
+        "date" => regex_match(r"^\d{4}-(0[1-9]|1[0-2])-(0[1-9]|[12]\d|3[01])$", s),
+        "email" => regex_match(r"^[^@\s]+@[^@\s]+\.[^@\s]+$", s),
+        "uuid" => regex_match(
+            r"^[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}$",
+            s,
+        ),
+        "uri" => regex_match(r"^[a-zA-Z][a-zA-Z0-9+.-]*:\S+$", s),
+        "ipv4" => s.parse::<std::net::Ipv4Addr>().is_ok(),
+        "ipv6" => s.parse::<std::net::Ipv6Addr>().is_ok(),
+        _ => true,
+    };
+    if valid {
+        None
+    } else {
+        Some(format!("{value} is not a valid {format}"))
+    }
+}
+
+fn regex_match(pattern: &str, s: &str) -> bool {
+    compiled_regex(pattern).is_ok_and(|re| re.is_match(s))
+}
+
+/// Escapes a JSON pointer reference token, see RFC 6901.
+fn escape(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SchemaValidator;
+    use crate::generator::Map;
+    use schemars::gen::SchemaGenerator;
+    use schemars::schema::SchemaObject;
+    use schemars::JsonSchema;
+    use serde_json::json;
+
+    #[derive(JsonSchema)]
+    #[allow(dead_code)]
+    pub struct TestVenue {
+        pub name: String,
+    }
+
+    #[derive(JsonSchema)]
+    #[allow(dead_code)]
+    #[serde(tag = "kind")]
+    pub enum TestTicket {
+        Free,
+        Paid { price: f64 },
+    }
+
+    #[derive(JsonSchema)]
+    #[allow(dead_code)]
+    pub struct TestEvent {
+        pub title: String,
+        pub seats: u32,
+        #[schemars(length(min = 1), regex(pattern = r"^[a-z]+$"))]
+        pub slug: String,
+        #[schemars(email)]
+        pub contact: String,
+        pub venues: Vec<TestVenue>,
+        pub ticket: TestTicket,
+        pub note: Option<String>,
+    }
+
+    fn schema_for<T: JsonSchema>() -> (SchemaObject, Map<String, SchemaObject>) {
+        let mut settings = crate::Oas3Builder::default_settings();
+        settings.option_nullable = true;
+        let mut gen = SchemaGenerator::new(settings);
+        let schema = gen.subschema_for::<T>().into_object();
+        let definitions = gen
+            .definitions()
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone().into_object()))
+            .collect();
+        (schema, definitions)
+    }
+
+    #[test]
+    fn test_validate() {
+        let (schema, definitions) = schema_for::<TestEvent>();
+        let validator = SchemaValidator::new(&definitions);
+
+        let valid = json!({
+            "title": "Hackaton",
+            "seats": 20,
+            "slug": "hackaton",
+            "contact": "team@example.com",
+            "venues": [{"name": "Hall"}],
+            "ticket": {"kind": "Paid", "price": 12.5},
+            "note": null
+        });
+        assert_eq!(Ok(()), validator.validate(&valid, &schema));
+
+        let invalid = json!({
+            "title": "Hackaton",
+            "seats": -1,
+            "slug": "Hackaton 2020",
+            "contact": "team",
+            "venues": [{"name": 3}],
+            "ticket": {"kind": "Paid"}
+        });
+        let errors: Vec<(String, &str)> = validator
+            .validate(&invalid, &schema)
+            .unwrap_err()
+            .into_iter()
+            .map(|e| (e.pointer, e.keyword))
+            .collect();
+        assert_eq!(
+            vec![
+                ("/contact".to_owned(), "format"),
+                ("/seats".to_owned(), "format"),
+                ("/seats".to_owned(), "minimum"),
+                ("/slug".to_owned(), "pattern"),
+                ("/ticket".to_owned(), "oneOf"),
+                ("/venues/0/name".to_owned(), "type"),
+            ],
+            errors
+        );
+    }
+
+    #[test]
+    fn test_pattern_and_multiple_of() {
+        let definitions = Map::default();
+        let validator = SchemaValidator::new(&definitions);
+        let schema: SchemaObject = serde_json::from_value(json!({
+            "type": "object",
+            "properties": {
+                "role": {"type": "string", "pattern": "^(?!admin).*$"},
+                "price": {"type": "number", "multipleOf": 0.1}
+            }
+        }))
+        .unwrap();
+        let valid = json!({"role": "admin", "price": 0.3});
+        assert_eq!(Ok(()), validator.validate(&valid, &schema));
+        let errors = validator
+            .validate(&json!({"price": 0.35}), &schema)
+            .unwrap_err();
+        assert_eq!("multipleOf", errors[0].keyword);
+    }
+
+    #[test]
+    fn test_missing_property() {
+        let (schema, definitions) = schema_for::<TestVenue>();
+        let errors = SchemaValidator::new(&definitions)
+            .validate(&json!({}), &schema)
+            .unwrap_err();
+        assert_eq!("/: missing required property `name`", errors[0].to_string());
+    }
+}
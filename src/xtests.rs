@@ -1,11 +1,15 @@
+use schemars::schema::SchemaObject;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use serde_skip::is_default;
-use std::collections::BTreeMap as Map;
+use std::collections::{BTreeMap as Map, BTreeSet};
+
+use crate::generator::{OpenApi, OpenApiGenerator, Operation};
+use crate::validation::SchemaValidator;
 
 /// The test in the "x-tests" extension.
 /// Implementation is based on 8c84cc6 of <https://github.com/davidkpiano/openapi-test>
-#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Clone)]
 pub struct Test {
     /// The description of the test, used for reporting the test results.
     pub description: String,
@@ -29,6 +33,26 @@ pub struct Test {
 
     #[serde(skip_serializing_if = "is_default", default)]
     pub required: Vec<String>,
+
+    #[serde(skip_serializing_if = "is_default", default)]
+    /// (optional) The name other tests refer to this test by in `depends_on`.
+    pub id: String,
+    #[serde(skip_serializing_if = "is_default", default)]
+    /// (optional) The ids of the tests that must run before this one, e.g. the create supplying a captured id.
+    pub depends_on: Vec<String>,
+    #[serde(skip_serializing_if = "is_default", default)]
+    /// (optional) Tests run in ascending order, then in document order (default: 0).
+    pub order: i32,
+    #[serde(skip_serializing_if = "is_default", default)]
+    /// (optional) Captures values of the response body into variables, e.g. "eid": "$.id".
+    /// Later tests and hooks use them as `{eid}` in params, query, headers, cookie and body strings.
+    pub capture: Map<String, String>,
+    #[serde(skip_serializing_if = "is_default", default)]
+    /// (optional) Requests to send before the test.
+    pub setup: Vec<Hook>,
+    #[serde(skip_serializing_if = "is_default", default)]
+    /// (optional) Requests to send after the test, even if it failed.
+    pub teardown: Vec<Hook>,
 }
 
 #[derive(Debug, Default, Serialize, Deserialize, PartialEq, Clone)]
@@ -53,4 +77,754 @@ pub struct Response {
     #[serde(skip_serializing_if = "is_default", default)]
     /// A status code (e.g., 200) or array of status codes (e.g., [200, 301]) that the expected response should match
     pub status: Vec<u16>,
+    #[serde(skip_serializing_if = "is_default", default)]
+    /// (optional) Assertions on the response body, all of them must hold.
+    pub body: Vec<BodyAssertion>,
+}
+
+impl Test {
+    /// Starts building a test described by `description`.
+    /// ```
+    /// use oas_gen::xtests::Test;
+    /// let test = Test::new("Fetch a document by key.")
+    ///     .auth()
+    ///     .param("key", "8472")
+    ///     .query("format", "json")
+    ///     .expect_status(200)
+    ///     .expect_header("content-type", "application/json")
+    ///     .build();
+    /// assert_eq!(vec![200], test.response.status);
+    /// ```
+    #[must_use]
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(description: &str) -> TestBuilder {
+        TestBuilder {
+            test: Test {
+                description: description.to_owned(),
+                ..Test::default()
+            },
+        }
+    }
+}
+
+/// Builds a `Test`, started by `Test::new`.
+#[derive(Debug, Default, Clone)]
+pub struct TestBuilder {
+    test: Test,
+}
+
+impl TestBuilder {
+    /// Names the test for `depends_on`.
+    #[must_use]
+    pub fn id(mut self, id: &str) -> Self {
+        id.clone_into(&mut self.test.id);
+        self
+    }
+
+    #[must_use]
+    pub fn auth(mut self) -> Self {
+        self.test.auth = true;
+        self
+    }
+
+    #[must_use]
+    pub fn skip(mut self) -> Self {
+        self.test.skip = true;
+        self
+    }
+
+    /// Supplies the value of the `{name}` path placeholder.
+    #[must_use]
+    pub fn param(mut self, name: &str, value: &str) -> Self {
+        self.test.params.insert(name.to_owned(), value.to_owned());
+        self
+    }
+
+    #[must_use]
+    pub fn query(mut self, name: &str, value: &str) -> Self {
+        self.test
+            .request
+            .query
+            .insert(name.to_owned(), value.to_owned());
+        self
+    }
+
+    #[must_use]
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.test
+            .request
+            .headers
+            .insert(name.to_owned(), value.to_owned());
+        self
+    }
+
+    #[must_use]
+    pub fn cookie(mut self, cookie: &str) -> Self {
+        cookie.clone_into(&mut self.test.request.cookie);
+        self
+    }
+
+    /// Sends `body` serialized as JSON.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if json serialization of `body` fail
+    #[must_use]
+    pub fn json_body<T: Serialize>(mut self, body: &T) -> Self {
+        self.test.request.body = serde_json::to_value(body).unwrap();
+        self
+    }
+
+    /// Adds `status` to the accepted response statuses.
+    #[must_use]
+    pub fn expect_status(mut self, status: u16) -> Self {
+        self.test.response.status.push(status);
+        self
+    }
+
+    #[must_use]
+    pub fn expect_header(mut self, name: &str, value: &str) -> Self {
+        self.test
+            .response
+            .headers
+            .insert(name.to_owned(), value.to_owned());
+        self
+    }
+
+    #[must_use]
+    pub fn expect_cookie(mut self, name: &str, value: &str) -> Self {
+        self.test
+            .response
+            .cookie
+            .insert(name.to_owned(), value.to_owned());
+        self
+    }
+
+    #[must_use]
+    pub fn expect_body(mut self, assertion: BodyAssertion) -> Self {
+        self.test.response.body.push(assertion);
+        self
+    }
+
+    /// Expects the property `name` in the response body.
+    #[must_use]
+    pub fn require(mut self, name: &str) -> Self {
+        self.test.required.push(name.to_owned());
+        self
+    }
+
+    /// Captures the value at `path` of the response body, e.g. "$.id", as `{name}`.
+    #[must_use]
+    pub fn capture(mut self, name: &str, path: &str) -> Self {
+        self.test.capture.insert(name.to_owned(), path.to_owned());
+        self
+    }
+
+    #[must_use]
+    pub fn depends_on(mut self, id: &str) -> Self {
+        self.test.depends_on.push(id.to_owned());
+        self
+    }
+
+    #[must_use]
+    pub fn order(mut self, order: i32) -> Self {
+        self.test.order = order;
+        self
+    }
+
+    #[must_use]
+    pub fn setup(mut self, hook: Hook) -> Self {
+        self.test.setup.push(hook);
+        self
+    }
+
+    #[must_use]
+    pub fn teardown(mut self, hook: Hook) -> Self {
+        self.test.teardown.push(hook);
+        self
+    }
+
+    #[must_use]
+    pub fn build(self) -> Test {
+        self.test
+    }
+}
+
+impl From<TestBuilder> for Test {
+    fn from(builder: TestBuilder) -> Self {
+        builder.build()
+    }
+}
+
+/// A request sent before or after a test, e.g. to create the document a fetch test reads.
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Clone)]
+pub struct Hook {
+    /// The HTTP method, e.g. "POST".
+    pub method: String,
+    /// The path, with `{name}` placeholders taken from the params and captured variables.
+    pub path: String,
+    #[serde(skip_serializing_if = "is_default", default)]
+    pub auth: bool,
+    #[serde(skip_serializing_if = "is_default", default)]
+    pub params: Map<String, String>,
+    #[serde(skip_serializing_if = "is_default", default)]
+    pub request: Request,
+    #[serde(skip_serializing_if = "is_default", default)]
+    /// (optional) Captures values of the response body into variables, like `Test::capture`.
+    pub capture: Map<String, String>,
+}
+
+/// An assertion on a response body.
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[serde(rename_all = "camelCase")]
+pub enum BodyAssertion {
+    /// The body equals the value.
+    Exact(Value),
+    /// The body contains the value: objects may have more properties, arrays more items.
+    Subset(Value),
+    /// The value at the JSON pointer, e.g. "/venue/name", equals the value.
+    PointerEquals(String, Value),
+    /// The body matches the schema documented for the response status.
+    MatchesSchema,
+    /// The body matches the schema.
+    Schema(Box<SchemaObject>),
+}
+
+impl BodyAssertion {
+    /// Checks `body` against the assertion, `documented` being the schema of the
+    /// response status used by `MatchesSchema`.
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` with the reason if the assertion does not hold.
+    pub fn check(
+        &self,
+        body: &Value,
+        documented: Option<&SchemaObject>,
+        validator: &SchemaValidator<'_>,
+    ) -> Result<(), String> {
+        let schema = match self {
+            BodyAssertion::Exact(expected) => {
+                return if body == expected {
+                    Ok(())
+                } else {
+                    Err(format!("expected the body {expected}, got {body}"))
+                };
+            }
+            BodyAssertion::Subset(expected) => {
+                return if is_subset(expected, body) {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "expected the body to contain {expected}, got {body}"
+                    ))
+                };
+            }
+            BodyAssertion::PointerEquals(pointer, expected) => {
+                return match body.pointer(pointer) {
+                    Some(actual) if actual == expected => Ok(()),
+                    Some(actual) => Err(format!("expected {expected} at {pointer}, got {actual}")),
+                    None => Err(format!("expected {expected} at {pointer}, got nothing")),
+                };
+            }
+            BodyAssertion::MatchesSchema => match documented {
+                Some(schema) => schema,
+                None => return Err("the response status has no documented schema".to_owned()),
+            },
+            BodyAssertion::Schema(schema) => schema,
+        };
+        validator.validate(body, schema).map_err(|errors| {
+            let errors: Vec<_> = errors.iter().map(ToString::to_string).collect();
+            format!("the body does not match the schema: {}", errors.join(", "))
+        })
+    }
+}
+
+/// Whether every property and item of `expected` is in `actual`; array items are
+/// matched in order against some item of `actual`.
+#[must_use]
+pub fn is_subset(expected: &Value, actual: &Value) -> bool {
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => expected
+            .iter()
+            .all(|(k, v)| actual.get(k).is_some_and(|a| is_subset(v, a))),
+        (Value::Array(expected), Value::Array(actual)) => {
+            let mut actual = actual.iter();
+            expected
+                .iter()
+                .all(|e| actual.by_ref().any(|a| is_subset(e, a)))
+        }
+        _ => expected == actual,
+    }
+}
+
+/// The value of `body` at `path`, either a JSON path of names and indexes like
+/// `$.items[0].id`, or a JSON pointer like `/items/0/id`.
+#[must_use]
+pub fn capture<'a>(body: &'a Value, path: &str) -> Option<&'a Value> {
+    if path.starts_with('/') || path.is_empty() {
+        return body.pointer(path);
+    }
+    let mut rest = path.strip_prefix('$')?;
+    let mut value = body;
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('.') {
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            value = value.get(&after[..end])?;
+            rest = &after[end..];
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let end = after.find(']')?;
+            let key = after[..end].trim_matches(|c| c == '\'' || c == '"');
+            value = match key.parse::<usize>() {
+                Ok(index) => value.get(index)?,
+                Err(_) => value.get(key)?,
+            };
+            rest = &after[end + 1..];
+        } else {
+            return None;
+        }
+    }
+    Some(value)
+}
+
+/// Replaces the `{name}` placeholders of `text` with the variables.
+///
+/// The text is scanned once, so placeholders inside substituted values are kept.
+#[must_use]
+pub fn substitute(text: &str, variables: &Map<String, String>) -> String {
+    let mut substituted = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        substituted.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let end = after.find('}');
+        if let Some((end, value)) = end.and_then(|end| Some((end, variables.get(&after[..end])?))) {
+            substituted.push_str(value);
+            rest = &after[end + 1..];
+        } else {
+            substituted.push('{');
+            rest = after;
+        }
+    }
+    substituted.push_str(rest);
+    substituted
+}
+
+/// Replaces the `{name}` placeholders of every string in `value` with the variables.
+#[must_use]
+pub fn substitute_value(value: &Value, variables: &Map<String, String>) -> Value {
+    match value {
+        Value::String(text) => Value::String(substitute(text, variables)),
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| substitute_value(item, variables))
+                .collect(),
+        ),
+        Value::Object(object) => Value::Object(
+            object
+                .iter()
+                .map(|(k, v)| (k.clone(), substitute_value(v, variables)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+/// A dependency of a test that can not be satisfied, returned by `order_tests`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TestOrderError {
+    /// The test depends on an id no test has.
+    UnknownDependency { test: String, dependency: String },
+    /// The tests depend on each other.
+    Cycle(Vec<String>),
+    /// More than one test has the id.
+    DuplicateId(String),
+}
+impl std::fmt::Display for TestOrderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TestOrderError::UnknownDependency { test, dependency } => {
+                write!(
+                    f,
+                    "The test `{test}` depends on the unknown test `{dependency}`"
+                )
+            }
+            TestOrderError::Cycle(tests) => {
+                write!(f, "The tests `{}` depend on each other", tests.join("`, `"))
+            }
+            TestOrderError::DuplicateId(id) => {
+                write!(f, "More than one test has the id `{id}`")
+            }
+        }
+    }
+}
+impl std::error::Error for TestOrderError {}
+
+/// The tests in running order: ascending `order`, then document order, each after
+/// the tests in its `depends_on`.
+///
+/// # Errors
+///
+/// Will return `Err` if two tests have the same id, a dependency is unknown or the
+/// dependencies form a cycle.
+pub fn order_tests<'a, I>(tests: I) -> Result<Vec<&'a Test>, TestOrderError>
+where
+    I: IntoIterator<Item = &'a Test>,
+{
+    let mut pending: Vec<&Test> = tests.into_iter().collect();
+    pending.sort_by_key(|test| test.order);
+    let mut ids = BTreeSet::new();
+    for test in pending.iter().filter(|test| !test.id.is_empty()) {
+        if !ids.insert(test.id.as_str()) {
+            return Err(TestOrderError::DuplicateId(test.id.clone()));
+        }
+    }
+    for test in &pending {
+        for dependency in &test.depends_on {
+            if !pending.iter().any(|t| &t.id == dependency) {
+                return Err(TestOrderError::UnknownDependency {
+                    test: test.description.clone(),
+                    dependency: dependency.clone(),
+                });
+            }
+        }
+    }
+
+    let mut ordered: Vec<&Test> = Vec::with_capacity(pending.len());
+    while !pending.is_empty() {
+        let ready = pending.iter().position(|test| {
+            test.depends_on
+                .iter()
+                .all(|dependency| ordered.iter().any(|t| &t.id == dependency))
+        });
+        let Some(ready) = ready else {
+            let tests = pending.iter().map(|t| t.description.clone()).collect();
+            return Err(TestOrderError::Cycle(tests));
+        };
+        ordered.push(pending.remove(ready));
+    }
+    Ok(ordered)
+}
+
+/// An operation of a spec with its `x-tests`, see `tested_operations`.
+pub(crate) struct TestedOperation {
+    pub(crate) path: String,
+    pub(crate) method: http::Method,
+    pub(crate) operation: Operation,
+    pub(crate) tests: Vec<Test>,
+}
+
+/// Every operation of `openapi` with its `x-tests`, no tests without the extension.
+///
+/// # Panics
+///
+/// Will panic if an `x-tests` extension is not a list of tests
+pub(crate) fn tested_operations(openapi: &OpenApi) -> Vec<TestedOperation> {
+    let mut operations = vec![];
+    for (path, path_item) in &openapi.paths {
+        for (method, op) in OpenApiGenerator::path_item_operation_refs(path_item) {
+            let tests: Vec<Test> = match op.extensions.get("x-tests") {
+                Some(tests) => serde_json::from_value(tests.clone())
+                    .unwrap_or_else(|e| panic!("Invalid x-tests of {} {}: {}", method, path, e)),
+                None => vec![],
+            };
+            operations.push(TestedOperation {
+                path: path.clone(),
+                method,
+                operation: op.clone(),
+                tests,
+            });
+        }
+    }
+    operations
+}
+
+/// The names of the variables captured by the tests of `operations` and their hooks.
+pub(crate) fn captured_names(operations: &[TestedOperation]) -> BTreeSet<String> {
+    let mut names = BTreeSet::new();
+    for test in operations.iter().flat_map(|op| &op.tests) {
+        let hooks = test.setup.iter().chain(&test.teardown);
+        names.extend(test.capture.keys().cloned());
+        names.extend(hooks.flat_map(|h| h.capture.keys().cloned()));
+    }
+    names
+}
+
+/// The operation of `operations` that `test` belongs to.
+///
+/// # Panics
+///
+/// Will panic if `test` is not one of the tests of `operations`
+pub(crate) fn operation_of<'a>(
+    operations: &'a [TestedOperation],
+    test: &Test,
+) -> &'a TestedOperation {
+    operations
+        .iter()
+        .find(|op| op.tests.iter().any(|t| std::ptr::eq(t, test)))
+        .expect("the ordered tests are from the operations")
+}
+
+/// The `x-tests` extension of the operation at `path`, empty without tests.
+///
+/// # Panics
+///
+/// Will panic if a test has a param without a placeholder in `path`, if a placeholder
+/// is neither a param nor captured, or if json serialization of `tests` fail.
+/// A placeholder counts as captured by another test of `tests`, a setup hook of the test
+/// or, as those may be of other operations, by the tests in its `depends_on`.
+pub(crate) fn tests_extensions(path: &str, tests: &[Test]) -> crate::generator::Map<String, Value> {
+    let mut extensions = crate::generator::Map::default();
+    if tests.is_empty() {
+        return extensions;
+    }
+    let placeholders: Vec<&str> = path
+        .split('{')
+        .skip(1)
+        .filter_map(|rest| rest.split('}').next())
+        .collect();
+    for test in tests {
+        for name in test.params.keys() {
+            assert!(
+                placeholders.contains(&name.as_str()),
+                "The test `{}` has the param `{}`, but {} has no such placeholder",
+                test.description,
+                name,
+                path
+            );
+        }
+        if !test.depends_on.is_empty() {
+            continue;
+        }
+        for placeholder in &placeholders {
+            let supplied = test.params.contains_key(*placeholder)
+                || tests
+                    .iter()
+                    .filter(|t| !std::ptr::eq(*t, test))
+                    .any(|t| t.capture.contains_key(*placeholder))
+                || test
+                    .setup
+                    .iter()
+                    .any(|h| h.capture.contains_key(*placeholder));
+            assert!(
+                supplied,
+                "The test `{}` has no param or captured variable for {{{}}} of {}",
+                test.description, placeholder, path
+            );
+        }
+    }
+    extensions.insert("x-tests".to_owned(), serde_json::to_value(tests).unwrap());
+    extensions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        capture, order_tests, substitute, BodyAssertion, Request, Response, Test, TestOrderError,
+    };
+    use crate::generator::Map;
+    use crate::{ApiId, ApiPath, Oas3Builder, SchemaValidator};
+    use serde_json::json;
+
+    fn test(id: &str, order: i32, depends_on: &[&str]) -> Test {
+        Test {
+            description: id.to_owned(),
+            id: id.to_owned(),
+            order,
+            depends_on: depends_on.iter().map(|d| (*d).to_owned()).collect(),
+            ..Test::default()
+        }
+    }
+
+    #[test]
+    fn test_serialization() {
+        let mut capture = std::collections::BTreeMap::default();
+        capture.insert("eid".to_owned(), "$.id".to_owned());
+        let create = Test {
+            description: "Create an event.".to_owned(),
+            id: "create".to_owned(),
+            capture,
+            response: Response {
+                status: vec![201],
+                body: vec![
+                    BodyAssertion::Subset(json!({"title": "Hackaton"})),
+                    BodyAssertion::PointerEquals("/seats".to_owned(), json!(20)),
+                    BodyAssertion::MatchesSchema,
+                ],
+                ..Response::default()
+            },
+            ..Test::default()
+        };
+        let value = serde_json::to_value(&create).unwrap();
+        assert_eq!(
+            json!({
+                "description": "Create an event.",
+                "id": "create",
+                "capture": {"eid": "$.id"},
+                "response": {
+                    "status": [201],
+                    "body": [
+                        {"subset": {"title": "Hackaton"}},
+                        {"pointerEquals": ["/seats", 20]},
+                        "matchesSchema"
+                    ]
+                }
+            }),
+            value
+        );
+        let back: Test = serde_json::from_value(value).unwrap();
+        assert_eq!(create.response, back.response);
+    }
+
+    #[test]
+    fn test_assertions() {
+        let definitions = Map::default();
+        let validator = SchemaValidator::new(&definitions);
+        let body = json!({"id": "e1", "title": "Hackaton", "tags": ["a", "b", "c"]});
+        let check = |assertion: BodyAssertion| assertion.check(&body, None, &validator);
+
+        assert_eq!(Ok(()), check(BodyAssertion::Exact(body.clone())));
+        assert_eq!(
+            Ok(()),
+            check(BodyAssertion::Subset(json!({"tags": ["a", "c"]})))
+        );
+        assert!(check(BodyAssertion::Subset(json!({"tags": ["c", "a"]}))).is_err());
+        assert_eq!(
+            Err("expected \"x\" at /title, got \"Hackaton\"".to_owned()),
+            check(BodyAssertion::PointerEquals(
+                "/title".to_owned(),
+                json!("x")
+            ))
+        );
+        assert!(check(BodyAssertion::MatchesSchema).is_err());
+
+        assert_eq!(Some(&json!("b")), capture(&body, "$.tags[1]"));
+        assert_eq!(Some(&json!("e1")), capture(&body, "$.id"));
+        assert_eq!(Some(&json!("e1")), capture(&body, "/id"));
+        assert_eq!(None, capture(&body, "$.venue"));
+    }
+
+    #[test]
+    fn test_builder() {
+        let built = Test::new("Create an event.")
+            .id("create")
+            .auth()
+            .json_body(&json!({"title": "Hackaton"}))
+            .expect_status(201)
+            .expect_body(BodyAssertion::MatchesSchema)
+            .capture("eid", "$.id")
+            .build();
+        let mut capture = std::collections::BTreeMap::default();
+        capture.insert("eid".to_owned(), "$.id".to_owned());
+        let expected = Test {
+            description: "Create an event.".to_owned(),
+            id: "create".to_owned(),
+            auth: true,
+            capture,
+            request: Request {
+                body: json!({"title": "Hackaton"}),
+                ..Request::default()
+            },
+            response: Response {
+                status: vec![201],
+                body: vec![BodyAssertion::MatchesSchema],
+                ..Response::default()
+            },
+            ..Test::default()
+        };
+        assert_eq!(expected, built);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "The test `Fetch.` has the param `id`, but /api/events/{eid} has no such placeholder"
+    )]
+    fn test_unknown_param() {
+        let event_path = ApiPath::new(
+            Some("api".to_owned()),
+            vec![ApiId::new("events", "{eid}")],
+            None,
+        );
+        let fetch = Test::new("Fetch.").param("id", "e1").build();
+        let mut oasb = Oas3Builder::default();
+        oasb.fetch_with_tests::<String, String>(&event_path, "Events".to_owned(), None, &[fetch]);
+    }
+
+    #[test]
+    fn test_substitute() {
+        let mut variables = Map::default();
+        variables.insert("a".to_owned(), "{b}".to_owned());
+        variables.insert("b".to_owned(), "x".to_owned());
+        assert_eq!("/{b}/x/{c}/{", substitute("/{a}/{b}/{c}/{", &variables));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "The test `Fetch.` has no param or captured variable for {eid} of /api/events/{eid}"
+    )]
+    fn test_missing_param() {
+        let event_path = ApiPath::new(
+            Some("api".to_owned()),
+            vec![ApiId::new("events", "{eid}")],
+            None,
+        );
+        let fetch = Test::new("Fetch.").build();
+        let mut oasb = Oas3Builder::default();
+        oasb.fetch_with_tests::<String, String>(&event_path, "Events".to_owned(), None, &[fetch]);
+    }
+
+    #[test]
+    fn test_captured_param() {
+        let event_path = ApiPath::new(
+            Some("api".to_owned()),
+            vec![ApiId::new("events", "{eid}")],
+            None,
+        );
+        let mut oasb = Oas3Builder::default();
+        let create = Test::new("Create.")
+            .id("create")
+            .param("eid", "e1")
+            .capture("eid", "$.id")
+            .build();
+        let fetch = Test::new("Fetch.").depends_on("create").build();
+        oasb.replace_with_tests::<String, String, String>(
+            &event_path,
+            "Events".to_owned(),
+            None,
+            &[create],
+        );
+        oasb.fetch_with_tests::<String, String>(&event_path, "Events".to_owned(), None, &[fetch]);
+    }
+
+    #[test]
+    fn test_order_tests() {
+        let tests = vec![
+            test("fetch", 0, &["create"]),
+            test("cleanup", 9, &[]),
+            test("create", 1, &[]),
+        ];
+        let ordered: Vec<_> = order_tests(&tests)
+            .unwrap()
+            .iter()
+            .map(|t| t.id.as_str())
+            .collect();
+        assert_eq!(vec!["create", "fetch", "cleanup"], ordered);
+
+        let cycle = vec![test("a", 0, &["b"]), test("b", 0, &["a"])];
+        assert_eq!(
+            Err(TestOrderError::Cycle(vec!["a".to_owned(), "b".to_owned()])),
+            order_tests(&cycle)
+        );
+        let duplicate = vec![test("a", 0, &[]), test("b", 0, &["a"]), test("a", 1, &[])];
+        assert_eq!(
+            Err(TestOrderError::DuplicateId("a".to_owned())),
+            order_tests(&duplicate)
+        );
+        let unknown = vec![test("a", 0, &["z"])];
+        assert!(matches!(
+            order_tests(&unknown),
+            Err(TestOrderError::UnknownDependency { .. })
+        ));
+    }
 }